@@ -0,0 +1,80 @@
+use std::fmt::Display;
+
+/// Implicit reference to a stitch, written as `Part.R9.s3` or `R9.s3` (in the current part).
+/// Points at the same spot a `mark` placed right after the `stitch`-th stitch of round `round` would.
+/// Stitches are counted as they are worked, so `inc` and `dec` count as one stitch each,
+/// and every stitch of a magic ring counts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StitchAddress {
+    pub part: Option<String>,
+    /// Round number, starting from 1
+    pub round: usize,
+    /// Stitch number within the round, starting from 1
+    pub stitch: usize,
+}
+
+impl StitchAddress {
+    /// Returns None if `source` is a plain label
+    pub fn parse(source: &str) -> Option<Self> {
+        let mut segments = source.rsplitn(3, '.');
+        let stitch = segments.next()?.strip_prefix("s")?.parse().ok()?;
+        let round = segments.next()?.strip_prefix("R")?.parse().ok()?;
+        let part = segments.next().map(|part| part.to_owned());
+        Some(Self {
+            part,
+            round,
+            stitch,
+        })
+    }
+
+    pub fn in_part(self, part: &str) -> Self {
+        Self {
+            part: Some(self.part.unwrap_or_else(|| part.to_owned())),
+            ..self
+        }
+    }
+}
+
+impl Display for StitchAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(part) = &self.part {
+            write!(f, "{part}.")?;
+        }
+        write!(f, "R{}.s{}", self.round, self.stitch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            StitchAddress::parse("Body.R9.s3"),
+            Some(StitchAddress {
+                part: Some("Body".into()),
+                round: 9,
+                stitch: 3
+            })
+        );
+        assert_eq!(
+            StitchAddress::parse("R12.s1"),
+            Some(StitchAddress {
+                part: None,
+                round: 12,
+                stitch: 1
+            })
+        );
+        assert_eq!(StitchAddress::parse("R12"), None);
+        assert_eq!(StitchAddress::parse("bruh"), None);
+        assert_eq!(StitchAddress::parse("Body.R9.3"), None);
+    }
+
+    #[test]
+    fn test_display_roundtrip() {
+        for source in ["Body.R9.s3", "R1.s10"] {
+            assert_eq!(StitchAddress::parse(source).unwrap().to_string(), source);
+        }
+    }
+}
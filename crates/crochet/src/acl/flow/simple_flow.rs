@@ -37,6 +37,7 @@ impl Flow for SimpleFlow {
         Some(ActionWithOrigin {
             action: self.next()?,
             origin: None,
+            path: Default::default(),
         })
     }
 
@@ -44,6 +45,7 @@ impl Flow for SimpleFlow {
         Some(ActionWithOrigin {
             action: self.peek()?,
             origin: None,
+            path: Default::default(),
         })
     }
 }
@@ -1,10 +1,18 @@
+mod address;
 mod flow;
 mod parsing;
 mod pattern;
 
+pub use address::StitchAddress;
 pub use flow::Flow;
 #[cfg(test)]
 pub use flow::simple_flow::SimpleFlow;
 
-pub use parsing::{Error as PatternError, PatternBuilder};
-pub use pattern::{Action, ActionWithOrigin, Label, Origin, Part, PatternAst};
+pub use parsing::{
+    Error as PatternError, PatternBuilder, Warning as PatternWarning, WarningCode,
+    errors::ErrorCode as PatternErrorCode, renumber_rounds,
+};
+pub use pattern::{
+    Action, ActionWithOrigin, Handedness, Label, Origin, Part, PatternAst, PatternIter, Repeat,
+    RepetitionPath, Round, Stuffing,
+};
@@ -1,4 +1,4 @@
-use crate::acl::pattern::ActionWithOrigin;
+use crate::acl::pattern::{ActionWithOrigin, Repeat};
 
 #[derive(Debug)]
 pub struct ActionSequence {
@@ -16,19 +16,34 @@ impl ActionSequence {
 
     pub fn append_repeated(&mut self, other: ActionSequence, times: u32) {
         self.actions.reserve(other.actions.len() * times as usize);
-        for _ in 0..times {
-            self.actions.append(&mut other.actions.clone());
+        for i in 0..times {
+            for action in &other.actions {
+                self.actions.push(repeated(action, i, times));
+            }
         }
     }
 
     pub fn push(&mut self, action: ActionWithOrigin) {
-        self.push_repeated(action, 1);
+        self.actions.push(action);
     }
 
     pub fn push_repeated(&mut self, action: ActionWithOrigin, times: u32) {
         self.actions.reserve(times as usize);
-        for _ in 0..times {
-            self.actions.push(action.clone());
+        for i in 0..times {
+            self.actions.push(repeated(&action, i, times));
         }
     }
 }
+
+/// Copy of the action, with the outermost repetition in its path
+fn repeated(action: &ActionWithOrigin, i: u32, times: u32) -> ActionWithOrigin {
+    let mut action = action.clone();
+    action.path.repeats.insert(
+        0,
+        Repeat {
+            index: i as usize + 1,
+            of: times as usize,
+        },
+    );
+    action
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use super::errors::{Error, ErrorCode};
+use crate::{
+    acl::{Action, ActionWithOrigin, Label, PatternAst},
+    graph_construction::ANCHORS_FOR_FO_LIMIT,
+};
+
+impl PatternAst {
+    /// Checks the pattern for mistakes the hook would otherwise only find while building the graph,
+    /// so they can be reported with the exact location.
+    ///
+    /// Follows the number of available anchors the same way the hook does. Stops at the first action
+    /// the hook rejects for a reason not checked here, that error is left for the hook to report.
+    /// Single loop stitches are not checked, every anchor is created by a stitch or a magic ring, so it always has a parent.
+    pub fn analyze(&self) -> Result<(), Error> {
+        let mut analysis = Analysis::default();
+        for part in &self.parts {
+            for action in &part.actions {
+                if !analysis.perform(action)? {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Analysis<'a> {
+    anchors: usize,
+    /// Was any node created in the current part
+    started: bool,
+    /// Anchors available at each mark
+    labels: HashMap<&'a Label, usize>,
+}
+
+impl<'a> Analysis<'a> {
+    /// Returns false where the hook is going to fail for a reason not checked by the analysis
+    fn perform(&mut self, action: &'a ActionWithOrigin) -> Result<bool, Error> {
+        use Action::*;
+        let error = |code| Err(Error::with_expected_origin(code, action.origin));
+
+        if !self.started && !matches!(action.action, BeginPart | EndPart | MR(_) | Color(_)) {
+            return error(ErrorCode::BadStarter);
+        }
+
+        match &action.action {
+            Sc => {
+                if self.anchors < 1 {
+                    return Ok(false);
+                }
+            }
+            Inc => {
+                if self.anchors < 1 {
+                    return Ok(false);
+                }
+                self.anchors += 1;
+            }
+            Dec => {
+                if self.anchors < 2 {
+                    return Ok(false);
+                }
+                self.anchors -= 1;
+            }
+            MR(count) => {
+                self.anchors = *count;
+                self.started = true;
+            }
+            FO => {
+                if self.anchors < 2 {
+                    return error(ErrorCode::FORequires2Anchors);
+                }
+                if self.anchors > ANCHORS_FOR_FO_LIMIT {
+                    return Ok(false);
+                }
+                self.anchors = 0;
+            }
+            Mark(label) => {
+                if self.anchors == 0 {
+                    return error(ErrorCode::UselessMark);
+                }
+                // labels written twice are rejected by the parser, so this mark was copied by a repetition
+                if self.labels.insert(label, self.anchors).is_some() {
+                    return error(ErrorCode::IllegalActionInRepetition);
+                }
+            }
+            MarkRingStitch(label, stitch) => {
+                self.labels.insert(label, *stitch);
+            }
+            // the anchor of the increase is still there, its second half is not yet
+            MarkFirstOfInc(label) => {
+                self.labels.insert(label, self.anchors);
+            }
+            Goto(label) => match self.labels.get(label) {
+                Some(anchors) => self.anchors = *anchors,
+                None => return Ok(false),
+            },
+            BeginPart | EndPart => {
+                self.anchors = 0;
+                self.started = false;
+            }
+            // slst is disabled in the hook
+            Slst | FLO | BLO | BL | Color(_) | Sew(_, _) | EnforceAnchors(_, _) => (),
+            Attach(_, _) => return Ok(false),
+        }
+        Ok(true)
+    }
+}
@@ -19,7 +19,8 @@ pub enum ErrorCode {
     /// Please report and attach pattern.
     Internal(String),
     /// Not a syntactically valid ACL program.
-    Lexer(pest::error::Error<Rule>),
+    /// Boxed because it is much larger than the other codes.
+    Lexer(Box<pest::error::Error<Rule>>),
     /// Unknown action
     UnknownAction(String),
     TooLittleArguments(Expected, Got),
@@ -27,6 +28,16 @@ pub enum ErrorCode {
     ExpectedInteger(String),
     /// Color value must be between 0 and 255 inclusive
     ExpectedRgbValue(String),
+    /// Handedness must be either "left" or "right"
+    ExpectedHandedness(String),
+    /// Flag must be one of "yes", "no", "true", "false"
+    ExpectedFlag(String),
+    /// Stuffing must be either "centroids" or "pressure"
+    ExpectedStuffing(String),
+    /// Firmness must be a positive number, e.g. "0.5"
+    ExpectedFirmness(String),
+    /// Pellets must be a number not smaller than 0, e.g. "2"
+    ExpectedPellets(String),
     /// Round range (e.g. "R1-R2:") uses wrong numbers. First number must be smaller than the second.
     InvalidRoundRange(String),
     /// Parameters names must be unique.
@@ -37,6 +48,8 @@ pub enum ErrorCode {
     DuplicateLabel(String),
     /// Tried to use a goto or a similar instruction to an undefined mark
     UndefinedLabel(String),
+    /// Address (e.g. "Body.R9.s3") does not point to a stitch worked on a previous line
+    UndefinedAddress(String),
     /// This action is not allowed inside a round.
     NotAllowedInRound(Action),
     /// This action is not allowed outside a round.
@@ -47,6 +60,14 @@ pub enum ErrorCode {
     NotRepeatable,
     /// Part names must be unique
     DuplicatePart(String),
+    /// Part must start with a magic ring
+    BadStarter,
+    /// Mark placed where there is nothing to work into, e.g. right after FO
+    UselessMark,
+    /// Fasten off needs at least 2 stitches to close
+    FORequires2Anchors,
+    /// Mark in a repeated round, every repetition would place the same mark
+    IllegalActionInRepetition,
 }
 
 impl Error {
@@ -57,7 +78,7 @@ impl Error {
         };
 
         Self {
-            code: ErrorCode::Lexer(e),
+            code: ErrorCode::Lexer(Box::new(e)),
             origin: Origin::from_start_end(start, end),
         }
     }
@@ -1,16 +1,21 @@
 mod action_sequence;
+mod analysis;
 pub mod errors;
 mod pattern_builder;
+mod renumber;
+pub mod warnings;
 
 use std::collections::{HashMap, HashSet};
 
 pub use errors::Error;
 use pest::Parser;
 use pest_derive::Parser;
+pub use renumber::renumber_rounds;
+pub use warnings::{Warning, WarningCode};
 
 use crate::acl::{
-    Origin, PatternAst,
-    pattern::{ActionWithOrigin, Part},
+    Handedness, Label, Origin, PatternAst, StitchAddress,
+    pattern::{ActionWithOrigin, Part, Round},
 };
 
 #[derive(Parser)]
@@ -28,6 +33,32 @@ pub struct PatternBuilder {
     labels: HashSet<String>,
     /// Kept for auto inserting BL at start of round
     current_loop: CurrentLoop,
+    /// Name of the part being parsed
+    current_part: String,
+    /// Number of the last round parsed in the current part
+    last_round: usize,
+    /// Handedness of parts that don't set their own, set before the first named part
+    default_handedness: Handedness,
+    /// Parameters written before the first named part, when no stitches are worked there. Every part starts with them.
+    default_parameters: HashMap<String, (String, Origin)>,
+    /// Collects rounds to be moved into Part
+    rounds_buffer: Vec<Round>,
+    /// Problems that do not prevent building the pattern
+    warnings: Vec<Warning>,
+    /// Stitches that can be referenced with an address
+    stitch_spots: HashMap<StitchAddress, StitchSpot>,
+    /// Marks to be inserted after referenced stitches
+    implicit_marks: HashMap<Label, StitchSpot>,
+}
+
+/// Action that worked a stitch, a mark placed right after it marks the stitch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct StitchSpot {
+    part: usize,
+    action: usize,
+    /// Which of the stitches made by the action, starting from 1, when a plain mark can't tell it apart.
+    /// Set for every stitch of a magic ring and for the first half of an increase.
+    worked: Option<usize>,
 }
 
 #[derive(Debug)]
@@ -45,13 +76,23 @@ impl PatternBuilder {
             actions_buffer: vec![],
             current_loop: CurrentLoop::Both,
             parts: vec![],
+            current_part: Default::default(),
+            last_round: 0,
+            default_handedness: Handedness::Right,
+            default_parameters: Default::default(),
+            rounds_buffer: vec![],
+            warnings: vec![],
+            stitch_spots: Default::default(),
+            implicit_marks: Default::default(),
         };
         let line_pairs = PatParser::parse(Rule::program, program).map_err(|e| Error::lexer(e))?;
         builder.program(line_pairs)?;
         assert_eq!(builder.actions_buffer.len(), 0);
+        builder.insert_implicit_marks();
 
         Ok(PatternAst {
             parts: builder.parts,
+            warnings: builder.warnings,
         })
     }
 }
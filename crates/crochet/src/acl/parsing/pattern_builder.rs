@@ -1,10 +1,11 @@
 use pest::iterators::{Pair, Pairs};
 
-use super::{CurrentLoop, PatternBuilder, Rule, errors::*};
+use super::{CurrentLoop, PatternBuilder, Rule, StitchSpot, errors::*};
 use crate::acl::{
-    ActionWithOrigin, Origin,
+    ActionWithOrigin, Handedness, Label, Origin, StitchAddress, Stuffing,
     parsing::action_sequence::ActionSequence,
-    pattern::{Action, Part, PartParameters},
+    parsing::warnings::{Warning, WarningCode},
+    pattern::{Action, Part, PartParameters, Round},
 };
 
 pub const ANONYMOUS_PART: &'static str = "anonymous_part";
@@ -16,8 +17,24 @@ impl PatternBuilder {
                 match pair.as_rule() {
                     Rule::part_body => {
                         assert!(self.parts.is_empty());
+                        let only_parameters = pair.clone().into_inner().all(|pair| {
+                            matches!(pair.as_rule(), Rule::parameter | Rule::comment | Rule::EOI)
+                        });
+                        if only_parameters {
+                            // parameters before the first named part are defaults of the whole pattern
+                            for parameter in pair.into_inner() {
+                                if parameter.as_rule() == Rule::parameter {
+                                    self.parameter(parameter.into_inner())?;
+                                }
+                            }
+                            self.default_parameters = std::mem::take(&mut self.parameters_buffer);
+                            continue;
+                        }
+                        self.current_part = ANONYMOUS_PART.into();
                         self.part_body(pair.into_inner())?;
                         self.register_part(ANONYMOUS_PART.into(), 1)?;
+                        // handedness set before the first named part is the default of the whole pattern
+                        self.default_handedness = self.parts[0].parameters.handedness;
                     }
                     Rule::part => self.part(pair.into_inner())?,
                     Rule::EOI => (),
@@ -50,6 +67,7 @@ impl PatternBuilder {
             ));
         }
 
+        self.current_part = part_name.clone();
         self.part_body(body_pair.into_inner())?;
         self.register_part(part_name, part_instances)?;
 
@@ -58,10 +76,12 @@ impl PatternBuilder {
 
     pub fn part_body(&mut self, pairs: Pairs<Rule>) -> Result<(), Error> {
         self.actions_buffer.push(Action::BeginPart.without_origin());
+        self.last_round = 0;
         for pair in pairs {
             match pair.as_rule() {
-                Rule::round => self.round(pair.into_inner())?,
-                Rule::comment => (),
+                Rule::round => self.round(pair)?,
+                // a comment on the last line consumes EOI
+                Rule::comment | Rule::EOI => (),
                 Rule::parameter => self.parameter(pair.into_inner())?,
                 Rule::controls_out_of_round => {
                     self.controls_out_of_round(pair.into_inner().next().unwrap().into_inner())?
@@ -74,58 +94,69 @@ impl PatternBuilder {
         Ok(())
     }
 
-    fn round(&mut self, mut pairs: Pairs<Rule>) -> Result<(), Error> {
+    fn round(&mut self, round_pair: Pair<Rule>) -> Result<(), Error> {
+        // the BL inserted by reset belongs to the first round
+        let mut start = self.actions_buffer.len();
         self.reset_to_both_loops();
 
+        let round_start = round_pair.as_span().start();
+        let mut pairs = round_pair.into_inner();
         let first = pairs.next().unwrap();
-        let (repetitions, stitches) = match first.as_rule() {
-            Rule::stitches => (1, first),
+        let (first_round, repetitions, label, stitches) = match first.as_rule() {
+            Rule::stitches => (self.last_round + 1, 1, None, first),
             Rule::round_repetition => {
                 let inner = first.into_inner().next().unwrap();
-                let number = match inner.as_rule() {
-                    Rule::NUMBER => integer(&inner)?,
+                let label = match inner.as_rule() {
+                    Rule::NUMBER => None,
+                    _ => Some(Origin::from_span(inner.as_span())),
+                };
+                let (first_round, number) = match inner.as_rule() {
+                    Rule::NUMBER => {
+                        let times = integer(&inner)?;
+                        if times == 0 {
+                            return Err(error(RepetitionTimes0, &inner));
+                        }
+                        (self.last_round + 1, times)
+                    }
                     Rule::round_range => {
                         let s = inner.as_str();
                         let (r1, r2) = s.split_once("-").expect("round_range has no '-'");
-                        let r_to_usize = |r: &str| -> usize {
+                        let origin = Origin::from_span(inner.as_span());
+                        let [n1, n2] = [r1, r2].map(|r| {
                             r.strip_prefix("R")
                                 .expect("round_range ::= R<int>-r<int> (R[1])")
-                                .parse()
-                                .expect("round_range ::= R<int>-r<int> (int[1])")
-                        };
-                        let n1 = r_to_usize(r1);
-                        let n2 = r_to_usize(r2);
+                        });
+                        let n1 = integer_from_str(n1, origin)?;
+                        let n2 = integer_from_str(n2, origin)?;
                         if n2 <= n1 {
                             return err(InvalidRoundRange(s.to_string()), &inner);
                         }
-                        n2 - n1 + 1
+                        (n1, n2 - n1 + 1)
+                    }
+                    Rule::round_index => {
+                        let origin = Origin::from_span(inner.as_span());
+                        (integer_from_str(&inner.as_str()[1..], origin)?, 1)
                     }
-                    Rule::round_index => 1,
                     _ => unreachable!(),
                 };
-                (number, pairs.next().unwrap())
+                (first_round, number, label, pairs.next().unwrap())
             }
             _ => unreachable!(),
         };
+        if let Some(label) = label {
+            self.validate_round_number(first_round, label);
+        }
 
+        let mut round_end = stitches.as_span().end();
         let action_sequence = self.stitches(stitches.into_inner())?;
 
-        for _ in 0..repetitions {
-            let mut to_append = action_sequence.actions().clone().into_iter().collect();
-            self.actions_buffer.append(&mut to_append);
-        }
-
+        let mut declared_count = None;
         match pairs.next() {
             Some(pair) => match pair.as_rule() {
                 Rule::round_end => {
-                    let round_end_pair = pair;
-                    let count_pair = round_end_pair.into_inner().next().unwrap();
-                    let count = integer(&count_pair)?;
-                    self.actions_buffer.push(
-                        // TODO remove line_col from this? - first make sure hook can report the location
-                        Action::EnforceAnchors(count, count_pair.line_col())
-                            .with_origin(count_pair.as_span()),
-                    );
+                    round_end = pair.as_span().end();
+                    let count_pair = pair.into_inner().next().unwrap();
+                    declared_count = Some((integer(&count_pair)?, count_pair));
                 }
                 Rule::comment => (),
                 Rule::EOI => (),
@@ -134,9 +165,63 @@ impl PatternBuilder {
             None => (),
         }
 
+        for round in first_round..first_round + repetitions {
+            let mut stitch = 0;
+            for action in action_sequence.actions() {
+                let mut action = action.clone();
+                action.path.round = Some(round);
+                let stitch_count = action.action.stitch_count();
+                if stitch_count > 0 {
+                    action.path.stitch = Some(stitch + 1);
+                }
+                // a mark right after the action only marks its last stitch
+                let marks_each = matches!(action.action, Action::MR(_));
+                self.actions_buffer.push(action);
+                for worked in 1..=stitch_count {
+                    stitch += 1;
+                    let needs_own_mark = marks_each || worked < stitch_count;
+                    self.register_stitch_spot(round, stitch, needs_own_mark.then_some(worked));
+                }
+            }
+            self.rounds_buffer.push(Round {
+                number: round,
+                label,
+                origin: Origin::from_start_end(round_start, round_end),
+                declared_count: declared_count.as_ref().map(|(count, _)| *count),
+                actions: start..self.actions_buffer.len(),
+            });
+            start = self.actions_buffer.len();
+        }
+        self.last_round = first_round + repetitions - 1;
+
+        if let Some((count, count_pair)) = declared_count {
+            self.actions_buffer.push(
+                // TODO remove line_col from this? - first make sure hook can report the location
+                Action::EnforceAnchors(count, count_pair.line_col())
+                    .with_origin(count_pair.as_span()),
+            );
+            // the check is performed at the end of the last repetition
+            self.rounds_buffer.last_mut().unwrap().actions.end += 1;
+        }
+
         Ok(())
     }
 
+    fn validate_round_number(&mut self, found: usize, label: Origin) {
+        let expected = self.last_round + 1;
+        let code = if found > expected {
+            WarningCode::RoundNumberGap { expected, found }
+        } else if found < expected {
+            WarningCode::RoundNumberReused { expected, found }
+        } else {
+            return;
+        };
+        self.warnings.push(Warning {
+            code,
+            origin: label,
+        });
+    }
+
     fn reset_to_both_loops(&mut self) {
         match self.current_loop {
             CurrentLoop::Back | CurrentLoop::Front => {
@@ -193,11 +278,11 @@ impl PatternBuilder {
                 Rule::action_sequence => {
                     for pair in first.into_inner() {
                         assert_eq!(pair.as_rule(), Rule::action);
-                        let action = action(pair)?;
+                        let mut action = action(pair)?;
 
                         use Action::*;
                         match &action.action {
-                            Goto(_) => self.use_mark(&action)?,
+                            Goto(_) => self.use_mark(&mut action)?,
                             Mark(_) => self.new_mark(&action)?,
                             FLO => self.current_loop = CurrentLoop::Front,
                             BLO => self.current_loop = CurrentLoop::Back,
@@ -212,14 +297,22 @@ impl PatternBuilder {
                             //             let chain_size = integer(&args.next().unwrap())?;
                             //             Action::Attach(label, chain_size)
                             //         }
-                            Attach(_, _) => todo!(),
+                            Attach(_, _) => {
+                                return Err(Error::internal("attach is not supported yet"));
+                            }
                             FO | Sew(_, _) => {
                                 return Err(Error::with_expected_origin(
                                     ErrorCode::NotAllowedInRound(action.action),
                                     action.origin,
                                 ));
                             }
-                            EnforceAnchors(_, _) | BeginPart | EndPart => unreachable!(),
+                            MarkRingStitch(_, _)
+                            | MarkFirstOfInc(_)
+                            | EnforceAnchors(_, _)
+                            | BeginPart
+                            | EndPart => {
+                                unreachable!()
+                            }
                         }
                         result.push(action);
                     }
@@ -245,11 +338,11 @@ impl PatternBuilder {
 
     fn controls_out_of_round(&mut self, pairs: Pairs<Rule>) -> Result<(), Error> {
         for pair in pairs {
-            let action = action(pair)?;
+            let mut action = action(pair)?;
 
             use Action::*;
-            match &action.action {
-                Goto(_) => self.use_mark(&action)?,
+            match &mut action.action {
+                Goto(_) => self.use_mark(&mut action)?,
                 Mark(_) => self.new_mark(&action)?,
                 FO | Color(_) => (),
                 Sc | Inc | Dec | Slst | FLO | BLO | BL | MR(_) => {
@@ -258,22 +351,16 @@ impl PatternBuilder {
                         action.origin,
                     ));
                 }
-                Attach(_, _) => todo!(),
+                Attach(_, _) => return Err(Error::internal("attach is not supported yet")),
                 Sew(mark_a, mark_b) => {
-                    if !self.labels.contains(mark_a) {
-                        return Err(Error::with_expected_origin(
-                            ErrorCode::UndefinedLabel(mark_a.clone()),
-                            action.origin,
-                        ));
-                    }
-                    if !self.labels.contains(mark_b) {
-                        return Err(Error::with_expected_origin(
-                            ErrorCode::UndefinedLabel(mark_b.clone()),
-                            action.origin,
-                        ));
-                    }
+                    self.use_label(mark_a, action.origin)?;
+                    self.use_label(mark_b, action.origin)?;
                 }
-                EnforceAnchors(_, _) | BeginPart | EndPart => unreachable!(),
+                MarkRingStitch(_, _)
+                | MarkFirstOfInc(_)
+                | EnforceAnchors(_, _)
+                | BeginPart
+                | EndPart => unreachable!(),
             }
 
             self.actions_buffer.push(action);
@@ -319,25 +406,134 @@ impl PatternBuilder {
         }
     }
 
-    fn use_mark(&mut self, goto_action: &ActionWithOrigin) -> Result<(), Error> {
-        if let Action::Goto(label) = &goto_action.action {
+    fn use_mark(&mut self, goto_action: &mut ActionWithOrigin) -> Result<(), Error> {
+        if let Action::Goto(label) = &mut goto_action.action {
+            self.use_label(label, goto_action.origin)
+        } else {
+            Err(Error::internal("expected goto action here"))
+        }
+    }
+
+    /// Accepts either a mark label or a stitch address.
+    /// Addresses are rewritten to a canonical label, that will be placed by an implicit mark.
+    fn use_label(&mut self, label: &mut Label, origin: Option<Origin>) -> Result<(), Error> {
+        let Some(address) = StitchAddress::parse(label) else {
             if !self.labels.contains(label) {
                 return Err(Error::with_expected_origin(
                     UndefinedLabel(label.clone()),
-                    goto_action.origin,
+                    origin,
                 ));
             }
-            Ok(())
-        } else {
-            Err(Error::internal("expected goto action here"))
+            return Ok(());
+        };
+
+        let address = address.in_part(&self.current_part);
+        let Some(spot) = self.stitch_spots.get(&address) else {
+            return Err(Error::with_expected_origin(
+                UndefinedAddress(label.clone()),
+                origin,
+            ));
+        };
+        *label = address.to_string();
+        self.implicit_marks.insert(label.clone(), *spot);
+        Ok(())
+    }
+
+    fn register_stitch_spot(&mut self, round: usize, stitch: usize, worked: Option<usize>) {
+        let address = StitchAddress {
+            part: Some(self.current_part.clone()),
+            round,
+            stitch,
+        };
+        let spot = StitchSpot {
+            part: self.parts.len(),
+            action: self.actions_buffer.len() - 1,
+            worked,
+        };
+        // a reused round number must not move an address that may already be in use
+        self.stitch_spots.entry(address).or_insert(spot);
+    }
+
+    /// Places a mark after each stitch that was referenced by an address.
+    pub(super) fn insert_implicit_marks(&mut self) {
+        let mut marks: Vec<(Label, StitchSpot)> = self.implicit_marks.drain().collect();
+        // inserting from the back keeps the remaining spots valid
+        marks.sort_by(|(_, a), (_, b)| b.cmp(a));
+        for (label, spot) in marks {
+            let part = &mut self.parts[spot.part];
+            let position = spot.action + 1;
+            let mark = match (&part.actions[spot.action].action, spot.worked) {
+                (Action::MR(_), Some(stitch)) => Action::MarkRingStitch(label, stitch),
+                (Action::Inc, Some(_)) => Action::MarkFirstOfInc(label),
+                _ => Action::Mark(label),
+            };
+            part.actions.insert(position, mark.without_origin());
+            for round in &mut part.rounds {
+                if round.actions.start >= position {
+                    round.actions.start += 1;
+                }
+                if round.actions.end >= position {
+                    round.actions.end += 1;
+                }
+            }
         }
     }
 
     fn register_part(&mut self, name: String, instances: usize) -> Result<(), Error> {
-        let mut params_map = std::mem::take(&mut self.parameters_buffer);
+        let mut params_map = self.default_parameters.clone();
+        params_map.extend(std::mem::take(&mut self.parameters_buffer));
         let mut parameters = PartParameters::default();
         if let Some((value, origin)) = params_map.remove("centroids") {
-            parameters.centroids = integer_from_str(&value, origin)?;
+            if value == "auto" {
+                parameters.auto_centroids = true;
+            } else {
+                parameters.centroids = integer_from_str(&value, origin)?;
+            }
+        }
+        parameters.handedness = match params_map.remove("handedness") {
+            Some((value, origin)) => match value.as_str() {
+                "left" => Handedness::Left,
+                "right" => Handedness::Right,
+                _ => return Err(Error::with_origin(ExpectedHandedness(value), origin)),
+            },
+            None => self.default_handedness,
+        };
+        if let Some((value, origin)) = params_map.remove("inside_out") {
+            parameters.inside_out = match value.as_str() {
+                "yes" | "true" => true,
+                "no" | "false" => false,
+                _ => return Err(Error::with_origin(ExpectedFlag(value), origin)),
+            };
+        }
+        let firmness = match params_map.remove("firmness") {
+            Some((value, origin)) => match value.parse::<f32>() {
+                Ok(firmness) if firmness > 0.0 => Some((firmness, origin)),
+                _ => return Err(Error::with_origin(ExpectedFirmness(value), origin)),
+            },
+            None => None,
+        };
+        if let Some((value, origin)) = params_map.remove("pellets") {
+            parameters.pellets = match value.parse::<f32>() {
+                Ok(pellets) if pellets >= 0.0 => pellets,
+                _ => return Err(Error::with_origin(ExpectedPellets(value), origin)),
+            };
+        }
+        if let Some((value, origin)) = params_map.remove("stuffing") {
+            parameters.stuffing = match value.as_str() {
+                "centroids" => Stuffing::Centroids,
+                "pressure" => Stuffing::Pressure {
+                    firmness: firmness.map_or(Stuffing::DEFAULT_FIRMNESS, |(firmness, _)| firmness),
+                },
+                _ => return Err(Error::with_origin(ExpectedStuffing(value), origin)),
+            };
+        }
+        if let Some((_, origin)) = firmness
+            && !matches!(parameters.stuffing, Stuffing::Pressure { .. })
+        {
+            self.warnings.push(Warning {
+                code: WarningCode::IgnoredParameter("firmness".into()),
+                origin,
+            });
         }
 
         parameters.other = params_map
@@ -350,6 +546,7 @@ impl PatternBuilder {
             instances,
             actions: std::mem::take(&mut self.actions_buffer),
             parameters,
+            rounds: std::mem::take(&mut self.rounds_buffer),
         };
         self.parts.push(part);
         Ok(())
@@ -499,6 +696,7 @@ fn action(pair: Pair<Rule>) -> Result<ActionWithOrigin, Error> {
     Ok(ActionWithOrigin {
         action,
         origin: Some(spec.ident_origin),
+        path: Default::default(),
     })
 }
 
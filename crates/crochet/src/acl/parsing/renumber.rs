@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use pest::Parser;
+
+use super::{Error, PatParser, PatternBuilder, Rule, pattern_builder::ANONYMOUS_PART};
+use crate::acl::StitchAddress;
+
+/// Rewrites round labels (e.g. "R4:", "R7-R8:") so that rounds of every part are numbered consecutively from R1.
+/// Meant to be used after rounds were inserted or deleted. Rounds without labels are counted, but left unlabeled.
+/// Addresses (e.g. "Body.R4.s3") are rewritten too, so they keep pointing at the same stitches.
+pub fn renumber_rounds(program: &str) -> Result<String, Error> {
+    let pattern = PatternBuilder::parse(program)?;
+
+    let mut replacements = vec![];
+    // (part, number as parsed) -> new number, a reused number refers to its first round
+    let mut renumbered_rounds: HashMap<(&str, usize), usize> = HashMap::new();
+    for part in &pattern.parts {
+        for (index, round) in part.rounds.iter().enumerate() {
+            renumbered_rounds
+                .entry((&part.name, round.number))
+                .or_insert(index + 1);
+        }
+        let mut expected = 1;
+        // rounds produced by the same line share the label
+        for line in part
+            .rounds
+            .chunk_by(|a, b| a.label.is_some() && a.label == b.label)
+        {
+            if let Some(label) = line[0].label {
+                let renumbered = match line.len() {
+                    1 => format!("R{expected}"),
+                    n => format!("R{}-R{}", expected, expected + n - 1),
+                };
+                replacements.push((label.as_range(), renumbered));
+            }
+            expected += line.len();
+        }
+    }
+
+    let mut current_part = ANONYMOUS_PART;
+    let pairs = PatParser::parse(Rule::program, program).map_err(Error::lexer)?;
+    for pair in pairs.flatten() {
+        match pair.as_rule() {
+            Rule::part_header => current_part = pair.into_inner().next().unwrap().as_str(),
+            Rule::ADDRESS => {
+                let address = StitchAddress::parse(pair.as_str())
+                    .expect("ADDRESS ::= (IDENT.)?R<int>.s<int>");
+                let part = address.part.as_deref().unwrap_or(current_part);
+                // parsing succeeded, so the address points at an existing round
+                let round = renumbered_rounds[&(part, address.round)];
+                if round != address.round {
+                    let renumbered = StitchAddress { round, ..address };
+                    replacements.push((
+                        pair.as_span().start()..pair.as_span().end(),
+                        renumbered.to_string(),
+                    ));
+                }
+            }
+            _ => (),
+        }
+    }
+    // labels and addresses were collected separately
+    replacements.sort_by_key(|(range, _)| range.start);
+
+    let mut result = program.to_owned();
+    for (range, renumbered) in replacements.into_iter().rev() {
+        result.replace_range(range, &renumbered);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::force_graph::Initializer;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_renumber_after_deleting_round() {
+        let prog = indoc! {"
+            R1: MR(6)
+            R2: 6 inc
+            R4-R5: 12 sc
+            : 12 sc
+            R7: 6 dec
+        "};
+        let expected = indoc! {"
+            R1: MR(6)
+            R2: 6 inc
+            R3-R4: 12 sc
+            : 12 sc
+            R6: 6 dec
+        "};
+        assert_eq!(renumber_rounds(prog).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_renumber_after_inserting_round() {
+        let prog = indoc! {"
+            == Body ==
+            R1: MR(6)
+            R2: 6 inc
+            R2: 12 sc
+            2: 12 sc
+            R5: 6 dec
+
+            == Head ==
+            R1: MR(6)
+            R3: 6 inc
+        "};
+        let expected = indoc! {"
+            == Body ==
+            R1: MR(6)
+            R2: 6 inc
+            R3: 12 sc
+            2: 12 sc
+            R6: 6 dec
+
+            == Head ==
+            R1: MR(6)
+            R2: 6 inc
+        "};
+        assert_eq!(renumber_rounds(prog).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_renumber_rewrites_addresses() {
+        let prog = indoc! {"
+            == Body ==
+            R1: MR(6)
+            R2: 6 inc
+            R4: 12 sc
+            R5: goto(R4.s12), 12 sc
+            FO
+
+            == Arm ==
+            R1: MR(6)
+            R3: 6 sc
+            FO
+            sew(R3.s1, Body.R4.s3)
+            sew(R1.s2, Body.R5.s1)
+        "};
+        let expected = indoc! {"
+            == Body ==
+            R1: MR(6)
+            R2: 6 inc
+            R3: 12 sc
+            R4: goto(R3.s12), 12 sc
+            FO
+
+            == Arm ==
+            R1: MR(6)
+            R2: 6 sc
+            FO
+            sew(R2.s1, Body.R3.s3)
+            sew(R1.s2, Body.R4.s1)
+        "};
+        let renumbered = renumber_rounds(prog).unwrap();
+        assert_eq!(renumbered, expected);
+
+        // the same stitches are sewn
+        let build = |prog: &str| crate::parse(prog, 1.0, &Initializer::OneByOne).unwrap().0;
+        let (before, after) = (build(prog), build(&renumbered));
+        assert_eq!(before.edges, after.edges);
+        assert_eq!(before.deferred_edges, after.deferred_edges);
+    }
+
+    #[test]
+    fn test_renumbered_pattern_has_no_warnings() {
+        let prog = indoc! {"
+            R2: MR(6)
+            R2-R4: 6 sc
+            R3: 6 sc
+        "};
+        assert!(!PatternBuilder::parse(prog).unwrap().warnings.is_empty());
+        let renumbered = renumber_rounds(prog).unwrap();
+        assert!(
+            PatternBuilder::parse(&renumbered)
+                .unwrap()
+                .warnings
+                .is_empty()
+        );
+    }
+}
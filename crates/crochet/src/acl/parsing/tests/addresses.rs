@@ -0,0 +1,227 @@
+use indoc::indoc;
+use pretty_assertions::assert_eq;
+
+use crate::acl::{Action, PatternBuilder, parsing::errors::ErrorCode};
+
+#[test]
+fn test_goto_address_places_implicit_mark() {
+    let prog = indoc! {"
+        : MR(6)
+        : 6 inc
+        : 12 sc
+        goto(R2.s6)
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    let actions: Vec<Action> = pat.parts[0]
+        .actions
+        .iter()
+        .map(|a| a.action.clone())
+        .collect();
+    let mark_index = actions
+        .iter()
+        .position(|a| *a == Action::Mark("anonymous_part.R2.s6".into()))
+        .unwrap();
+    // BeginPart, MR, inc, inc, inc, mark
+    assert_eq!(mark_index, 5);
+    assert_eq!(pat.parts[0].actions[mark_index].origin, None);
+    assert_eq!(
+        actions.iter().rev().nth(1).unwrap(),
+        &Action::Goto("anonymous_part.R2.s6".into())
+    );
+}
+
+#[test]
+fn test_round_repetition_numbers_rounds() {
+    let prog = indoc! {"
+        : MR(6)
+        3: 6 sc
+        goto(R4.s6)
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    let mark_index = pat.parts[0]
+        .actions
+        .iter()
+        .position(|a| a.action == Action::Mark("anonymous_part.R4.s6".into()))
+        .unwrap();
+    assert_eq!(mark_index, 2 + 6 * 3);
+}
+
+#[test]
+fn test_explicit_round_numbers() {
+    let prog = indoc! {"
+        R1: MR(6)
+        R2-R3: 6 sc
+        R4: 6 sc
+        goto(R3.s1)
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    let mark_index = pat.parts[0]
+        .actions
+        .iter()
+        .position(|a| a.action == Action::Mark("anonymous_part.R3.s1".into()))
+        .unwrap();
+    assert_eq!(mark_index, 2 + 6 + 1);
+}
+
+#[test]
+fn test_sew_across_parts() {
+    let prog = indoc! {"
+        == Body ==
+        : MR(6)
+        : 6 inc
+        FO
+
+        == Arm ==
+        : MR(6)
+        : 6 sc
+        FO
+        sew(R2.s4, Body.R2.s6)
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    assert_eq!(
+        pat.parts[1].actions.iter().rev().nth(1).unwrap().action,
+        Action::Sew("Arm.R2.s4".into(), "Body.R2.s6".into())
+    );
+    assert!(
+        pat.parts[0]
+            .actions
+            .iter()
+            .any(|a| a.action == Action::Mark("Body.R2.s6".into()))
+    );
+    assert!(
+        pat.parts[1]
+            .actions
+            .iter()
+            .any(|a| a.action == Action::Mark("Arm.R2.s4".into()))
+    );
+}
+
+#[test]
+fn test_address_referenced_twice_is_marked_once() {
+    let prog = indoc! {"
+        == Body ==
+        : MR(6)
+        : 6 sc
+        sew(R2.s1, Body.R2.s2)
+        sew(Body.R2.s1, R2.s3)
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    let marks = pat.parts[0]
+        .actions
+        .iter()
+        .filter(|a| a.action == Action::Mark("Body.R2.s1".into()))
+        .count();
+    assert_eq!(marks, 1);
+}
+
+#[test]
+fn test_error_undefined_address() {
+    let prog = indoc! {"
+        : MR(6)
+        : 6 sc
+        goto(R2.s7)
+    "};
+    let err = PatternBuilder::parse(prog).unwrap_err();
+    assert_eq!(err.code, ErrorCode::UndefinedAddress("R2.s7".into()));
+    assert_eq!(&prog[err.origin.as_range()], "goto");
+
+    let prog = indoc! {"
+        == Body ==
+        : MR(6)
+        sew(Head.R1.s1, R1.s1)
+    "};
+    let err = PatternBuilder::parse(prog).unwrap_err();
+    assert_eq!(err.code, ErrorCode::UndefinedAddress("Head.R1.s1".into()));
+}
+
+#[test]
+fn test_reused_round_keeps_first_address() {
+    let prog = indoc! {"
+        R1: MR(6)
+        R1: goto(R1.s1), 6 sc, goto(R1.s1)
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    let marks: Vec<usize> = pat.parts[0]
+        .actions
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| a.action == Action::MarkRingStitch("anonymous_part.R1.s1".into(), 1))
+        .map(|(i, _)| i)
+        .collect();
+    // BeginPart, MR, mark, both gotos go to the same spot
+    assert_eq!(marks, vec![2]);
+}
+
+#[test]
+fn test_every_ring_stitch_has_an_address() {
+    let prog = indoc! {"
+        R1: MR(6)
+        R2: 6 inc
+        goto(R1.s3)
+        goto(R1.s6)
+        goto(R2.s1)
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    let actions: Vec<Action> = pat.parts[0]
+        .actions
+        .iter()
+        .map(|a| a.action.clone())
+        .collect();
+    let marks_after_ring: Vec<Action> = actions[2..4].to_vec();
+    assert!(marks_after_ring.contains(&Action::MarkRingStitch("anonymous_part.R1.s3".into(), 3)));
+    assert!(marks_after_ring.contains(&Action::MarkRingStitch("anonymous_part.R1.s6".into(), 6)));
+    // BeginPart, MR, 2 marks, inc
+    assert_eq!(
+        actions[5],
+        Action::MarkFirstOfInc("anonymous_part.R2.s1".into())
+    );
+}
+
+#[test]
+fn test_every_half_of_inc_has_an_address() {
+    let prog = indoc! {"
+        R1: MR(6)
+        R2: 6 inc (12)
+        R3: [sc, inc] x 3, 6 sc (15)
+        goto(R2.s12)
+        goto(R2.s7)
+        goto(R3.s9)
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    let actions: Vec<Action> = pat.parts[0]
+        .actions
+        .iter()
+        .map(|a| a.action.clone())
+        .collect();
+    let mark_after = |label: &str| {
+        let mark = actions
+            .iter()
+            .position(|a| match a {
+                Action::Mark(l) | Action::MarkFirstOfInc(l) => l == label,
+                _ => false,
+            })
+            .unwrap();
+        (actions[mark - 1].clone(), actions[mark].clone())
+    };
+    assert_eq!(
+        mark_after("anonymous_part.R2.s12"),
+        (Action::Inc, Action::Mark("anonymous_part.R2.s12".into()))
+    );
+    assert_eq!(
+        mark_after("anonymous_part.R2.s7"),
+        (
+            Action::Inc,
+            Action::MarkFirstOfInc("anonymous_part.R2.s7".into())
+        )
+    );
+    assert_eq!(
+        mark_after("anonymous_part.R3.s9"),
+        (Action::Inc, Action::Mark("anonymous_part.R3.s9".into()))
+    );
+    assert_eq!(
+        PatternBuilder::parse(": MR(6)\n: 6 inc (12)\ngoto(R2.s13)")
+            .unwrap_err()
+            .code,
+        ErrorCode::UndefinedAddress("R2.s13".into())
+    );
+}
@@ -0,0 +1,74 @@
+use crate::acl::{PatternBuilder, parsing::errors::ErrorCode};
+use pretty_assertions::assert_eq;
+
+fn analyze(prog: &str) -> Result<(), (ErrorCode, &str)> {
+    PatternBuilder::parse(prog)
+        .unwrap()
+        .analyze()
+        .map_err(|e| (e.code, &prog[e.origin.as_range()]))
+}
+
+#[test]
+fn test_valid_pattern() {
+    let prog = "
+        R1: MR(6)
+        R2: 6 inc, mark(a)
+        R3: FLO, [sc, dec] x 4
+        FO
+        goto(a)
+        R4: BLO, 12 sc
+        FO";
+    assert_eq!(analyze(prog), Ok(()));
+}
+
+#[test]
+fn test_bad_starter() {
+    assert_eq!(analyze(": 6 sc"), Err((ErrorCode::BadStarter, "sc")));
+    let prog = "
+        == Body ==
+        : MR(6)
+        : 6 sc, mark(a)
+        == Arm ==
+        goto(a)
+        : 6 sc";
+    assert_eq!(analyze(prog), Err((ErrorCode::BadStarter, "goto")));
+}
+
+#[test]
+fn test_useless_mark() {
+    let prog = "
+        : MR(6)
+        FO
+        mark(a)";
+    assert_eq!(analyze(prog), Err((ErrorCode::UselessMark, "mark")));
+}
+
+#[test]
+fn test_fo_requires_2_anchors() {
+    let prog = "
+        : MR(3)
+        : dec, dec
+        FO";
+    assert_eq!(analyze(prog), Err((ErrorCode::FORequires2Anchors, "FO")));
+}
+
+#[test]
+fn test_mark_in_repeated_round() {
+    let prog = "
+        R1: MR(6)
+        R2-R3: 6 sc, mark(a)";
+    assert_eq!(
+        analyze(prog),
+        Err((ErrorCode::IllegalActionInRepetition, "mark"))
+    );
+}
+
+#[test]
+fn test_stops_at_errors_left_for_hook() {
+    // running out of anchors is reported by the hook, FO would only fail afterwards
+    let prog = "
+        : MR(2)
+        : 3 sc
+        FO";
+    assert_eq!(analyze(prog), Ok(()));
+}
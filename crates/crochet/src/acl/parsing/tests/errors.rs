@@ -75,3 +75,21 @@ fn test_sew() {
         ErrorCode::NotAllowedInRound(crate::acl::Action::Sew("bruh".into(), "broh".into()))
     );
 }
+
+#[test]
+fn test_error_round_repeated_0_times() {
+    let prog = "R1: MR(6)\n0: 6 sc";
+    assert_eq!(
+        PatternBuilder::parse(prog).unwrap_err().code,
+        ErrorCode::RepetitionTimes0
+    );
+}
+
+#[test]
+fn test_error_round_number_too_big() {
+    let prog = "R1: MR(6)\nR99999999999999999999999: 6 sc";
+    assert_eq!(
+        PatternBuilder::parse(prog).unwrap_err().code,
+        ErrorCode::ExpectedInteger("99999999999999999999999".into())
+    );
+}
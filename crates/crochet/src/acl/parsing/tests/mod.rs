@@ -1,6 +1,9 @@
+mod addresses;
+mod analysis;
 mod control;
 mod errors;
 mod multiple_parts;
 mod origins;
 mod round;
+mod round_numbers;
 mod valid_programs;
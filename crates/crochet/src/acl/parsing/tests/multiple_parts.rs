@@ -3,8 +3,8 @@ use pretty_assertions::assert_eq;
 use crate::{
     PatternBuilder,
     acl::{
-        Action,
-        parsing::{errors::ErrorCode, pattern_builder::ANONYMOUS_PART},
+        Action, Handedness, Stuffing,
+        parsing::{errors::ErrorCode, pattern_builder::ANONYMOUS_PART, warnings::WarningCode},
     },
 };
 
@@ -160,3 +160,216 @@ fn test_working_loop_reset_does_not_happen_between_parts() {
     assert_eq!(pattern.parts[1].actions[0].action, Action::BeginPart);
     assert_eq!(pattern.parts[1].actions[1].action, Action::MR(7));
 }
+
+#[test]
+fn test_handedness_before_parts_is_the_default() {
+    let source = indoc::indoc! {"
+        @handedness = left
+        == Body ==
+        : MR(6)
+        == Arm ==
+        @handedness = right
+        : MR(6)
+        == Leg ==
+        : MR(6)
+    "};
+    let pattern = PatternBuilder::parse(source).unwrap();
+    let handedness: Vec<Handedness> = pattern
+        .parts
+        .iter()
+        .map(|part| part.parameters.handedness)
+        .collect();
+    use Handedness::*;
+    // no stitches before the first part, so there is no anonymous part
+    assert_eq!(handedness, vec![Left, Right, Left]);
+}
+
+#[test]
+fn test_parameters_before_parts_are_defaults() {
+    let source = indoc::indoc! {"
+        # pattern-wide settings
+        @handedness = left
+        @stuffing = pressure
+
+        == Body ==
+        : MR(6)
+        == Head ==
+        @stuffing = centroids
+        : MR(6)
+    "};
+    let pattern = PatternBuilder::parse(source).unwrap();
+    let names: Vec<&str> = pattern
+        .parts
+        .iter()
+        .map(|part| part.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["Body", "Head"]);
+    let parameters: Vec<(Handedness, Stuffing)> = pattern
+        .parts
+        .iter()
+        .map(|part| (part.parameters.handedness, part.parameters.stuffing))
+        .collect();
+    assert_eq!(
+        parameters,
+        vec![
+            (
+                Handedness::Left,
+                Stuffing::Pressure {
+                    firmness: Stuffing::DEFAULT_FIRMNESS
+                }
+            ),
+            (Handedness::Left, Stuffing::Centroids),
+        ]
+    );
+}
+
+#[test]
+fn test_handedness_is_set_per_part() {
+    let source = indoc::indoc! {"
+        == Body ==
+        @handedness = left
+        : MR(6)
+        == Arm ==
+        : MR(6)
+    "};
+    let pattern = PatternBuilder::parse(source).unwrap();
+    let handedness: Vec<Handedness> = pattern
+        .parts
+        .iter()
+        .map(|part| part.parameters.handedness)
+        .collect();
+    use Handedness::*;
+    assert_eq!(handedness, vec![Left, Right]);
+}
+
+#[test]
+fn test_unknown_handedness() {
+    let source = "@handedness = both\n: MR(6)";
+    assert_eq!(
+        PatternBuilder::parse(source).unwrap_err().code,
+        ErrorCode::ExpectedHandedness("both".into())
+    );
+}
+
+#[test]
+fn test_inside_out_is_set_per_part() {
+    let source = indoc::indoc! {"
+        == Head ==
+        @inside_out = yes
+        : MR(6)
+        == Arm ==
+        : MR(6)
+    "};
+    let pattern = PatternBuilder::parse(source).unwrap();
+    assert!(pattern.parts[0].parameters.inside_out);
+    assert!(!pattern.parts[1].parameters.inside_out);
+
+    let source = "@inside_out = maybe\n: MR(6)";
+    assert_eq!(
+        PatternBuilder::parse(source).unwrap_err().code,
+        ErrorCode::ExpectedFlag("maybe".into())
+    );
+}
+
+#[test]
+fn test_stuffing_is_set_per_part() {
+    let source = indoc::indoc! {"
+        == Head ==
+        @stuffing = pressure
+        @firmness = 1.5
+        : MR(6)
+        == Body ==
+        @stuffing = pressure
+        : MR(6)
+        == Arm ==
+        : MR(6)
+    "};
+    let pattern = PatternBuilder::parse(source).unwrap();
+    let stuffing: Vec<Stuffing> = pattern
+        .parts
+        .iter()
+        .map(|part| part.parameters.stuffing)
+        .collect();
+    assert_eq!(
+        stuffing,
+        vec![
+            Stuffing::Pressure { firmness: 1.5 },
+            Stuffing::Pressure {
+                firmness: Stuffing::DEFAULT_FIRMNESS
+            },
+            Stuffing::Centroids,
+        ]
+    );
+}
+
+#[test]
+fn test_firmness_without_pressure_is_a_warning() {
+    let source = indoc::indoc! {"
+        == Head ==
+        @stuffing = pressure
+        @firmness = 1.5
+        : MR(6)
+        == Body ==
+        @firmness = 2
+        : MR(6)
+    "};
+    let pattern = PatternBuilder::parse(source).unwrap();
+    assert_eq!(pattern.warnings.len(), 1);
+    let warning = &pattern.warnings[0];
+    assert_eq!(
+        warning.code,
+        WarningCode::IgnoredParameter("firmness".into())
+    );
+    assert_eq!(&source[warning.origin.as_range()], "2");
+    assert_eq!(pattern.parts[1].parameters.stuffing, Stuffing::Centroids);
+}
+
+#[test]
+fn test_unknown_stuffing() {
+    let source = "@stuffing = beans\n: MR(6)";
+    assert_eq!(
+        PatternBuilder::parse(source).unwrap_err().code,
+        ErrorCode::ExpectedStuffing("beans".into())
+    );
+
+    let source = "@stuffing = pressure\n@firmness = 0\n: MR(6)";
+    assert_eq!(
+        PatternBuilder::parse(source).unwrap_err().code,
+        ErrorCode::ExpectedFirmness("0".into())
+    );
+}
+
+#[test]
+fn test_automatic_centroids() {
+    let source = indoc::indoc! {"
+        == Stem ==
+        @centroids = auto
+        : MR(6)
+
+        == Cap ==
+        @centroids = 1
+        : MR(7)
+    "};
+    let pattern = PatternBuilder::parse(source).unwrap();
+    assert!(pattern.parts[0].parameters.auto_centroids);
+    assert!(!pattern.parts[1].parameters.auto_centroids);
+    assert_eq!(pattern.parts[1].parameters.centroids, 1);
+
+    let source = "@centroids = many\n: MR(6)";
+    assert_eq!(
+        PatternBuilder::parse(source).unwrap_err().code,
+        ErrorCode::ExpectedInteger("many".into())
+    );
+}
+
+#[test]
+fn test_pellets() {
+    let pattern = PatternBuilder::parse("@pellets = 1.5\n: MR(6)").unwrap();
+    assert_eq!(pattern.parts[0].parameters.pellets, 1.5);
+
+    let source = "@pellets = -1\n: MR(6)";
+    assert_eq!(
+        PatternBuilder::parse(source).unwrap_err().code,
+        ErrorCode::ExpectedPellets("-1".into())
+    );
+}
@@ -1,7 +1,7 @@
 use indoc::indoc;
 use pretty_assertions::assert_eq;
 
-use crate::acl::{Action, PatternBuilder};
+use crate::acl::{Action, PatternBuilder, Repeat, RepetitionPath};
 
 #[test]
 fn test_mr() {
@@ -211,3 +211,57 @@ fn test_round_repetition() {
         5..7
     );
 }
+
+#[test]
+fn test_repetition_path() {
+    let prog = indoc! {"
+        : MR(6)
+        : [sc, 2 inc] x 2
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    let actions = &pat.parts[0].actions;
+    // BeginPart, MR, sc, inc, inc, sc, inc, inc
+    // every inc counts as 2 stitches
+    assert_eq!(actions[1].path.round, Some(1));
+    assert_eq!(actions[1].path.stitch, Some(1));
+    assert_eq!(actions[1].path.repeats, vec![]);
+    assert_eq!(actions[4].origin, actions[7].origin);
+    assert_eq!(
+        actions[4].path,
+        RepetitionPath {
+            round: Some(2),
+            repeats: vec![Repeat { index: 1, of: 2 }, Repeat { index: 2, of: 2 }],
+            stitch: Some(4),
+        }
+    );
+    assert_eq!(
+        actions[7].path,
+        RepetitionPath {
+            round: Some(2),
+            repeats: vec![Repeat { index: 2, of: 2 }, Repeat { index: 2, of: 2 }],
+            stitch: Some(9),
+        }
+    );
+    assert_eq!(
+        actions[7].path.to_string(),
+        "R2, repeat 2 of 2, repeat 2 of 2, stitch 9"
+    );
+}
+
+#[test]
+fn test_repetition_path_of_repeated_round() {
+    let prog = indoc! {"
+        : MR(6)
+        2: 6 sc
+        : 6 sc, mark(a)
+        goto(a)
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    let actions = &pat.parts[0].actions;
+    assert_eq!(actions[13].path.round, Some(3));
+    assert_eq!(actions[13].path.stitch, Some(6));
+    assert_eq!(actions[20].action, Action::Mark("a".into()));
+    assert_eq!(actions[20].path.round, Some(4));
+    assert_eq!(actions[20].path.stitch, None);
+    assert_eq!(actions[21].path, RepetitionPath::default());
+}
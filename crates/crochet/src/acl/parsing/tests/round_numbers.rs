@@ -0,0 +1,169 @@
+use indoc::indoc;
+use pretty_assertions::assert_eq;
+
+use crate::acl::{
+    Action::{self, *},
+    ActionWithOrigin, PatternBuilder, WarningCode,
+};
+
+#[test]
+fn test_rounds_are_kept_on_part() {
+    let prog = indoc! {"
+        R1: MR(6)
+        R2-R3: 6 sc
+        2: 6 sc
+        : 6 sc
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    let numbers: Vec<usize> = pat.parts[0].rounds.iter().map(|r| r.number).collect();
+    assert_eq!(numbers, vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(
+        &prog[pat.parts[0].rounds[1].label.unwrap().as_range()],
+        "R2-R3"
+    );
+    assert_eq!(pat.parts[0].rounds[1].label, pat.parts[0].rounds[2].label);
+    assert_eq!(pat.parts[0].rounds[3].label, None);
+    assert_eq!(pat.warnings, vec![]);
+}
+
+#[test]
+fn test_round_numbers_restart_in_every_part() {
+    let prog = indoc! {"
+        == Body ==
+        R1: MR(6)
+        R2: 6 sc
+        == Head ==
+        R1: MR(6)
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    assert_eq!(pat.parts[1].rounds[0].number, 1);
+    assert_eq!(pat.warnings, vec![]);
+}
+
+#[test]
+fn test_warning_round_number_gap() {
+    let prog = indoc! {"
+        R1: MR(6)
+        R5: 6 sc
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    assert_eq!(pat.warnings.len(), 1);
+    assert_eq!(
+        pat.warnings[0].code,
+        WarningCode::RoundNumberGap {
+            expected: 2,
+            found: 5
+        }
+    );
+    assert_eq!(&prog[pat.warnings[0].origin.as_range()], "R5");
+}
+
+#[test]
+fn test_warning_first_round_is_not_1() {
+    let prog = ": MR(6)";
+    assert_eq!(PatternBuilder::parse(prog).unwrap().warnings, vec![]);
+
+    let prog = "R2: MR(6)";
+    let pat = PatternBuilder::parse(prog).unwrap();
+    assert_eq!(
+        pat.warnings[0].code,
+        WarningCode::RoundNumberGap {
+            expected: 1,
+            found: 2
+        }
+    );
+}
+
+#[test]
+fn test_warning_duplicate_round() {
+    let prog = indoc! {"
+        R1: MR(6)
+        R2: 6 sc
+        R2: 6 sc
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    assert_eq!(
+        pat.warnings[0].code,
+        WarningCode::RoundNumberReused {
+            expected: 3,
+            found: 2
+        }
+    );
+}
+
+#[test]
+fn test_warning_misordered_range() {
+    let prog = indoc! {"
+        R1: MR(6)
+        R2-R5: 6 sc
+        R3-R4: 6 sc
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    assert_eq!(pat.warnings.len(), 1);
+    assert_eq!(
+        pat.warnings[0].code,
+        WarningCode::RoundNumberReused {
+            expected: 6,
+            found: 3
+        }
+    );
+    assert_eq!(&prog[pat.warnings[0].origin.as_range()], "R3-R4");
+}
+
+#[test]
+fn test_round_structure() {
+    let prog = indoc! {"
+        R1: MR(6) (6)
+        R2-R3: BLO, 6 sc
+        R4: mark(a), 6 inc (12)
+        sew(a, R1.s1)
+    "};
+    let pat = PatternBuilder::parse(prog).unwrap();
+    let part = &pat.parts[0];
+    let round_sources: Vec<&str> = part
+        .rounds
+        .iter()
+        .map(|r| &prog[r.origin.as_range()])
+        .collect();
+    assert_eq!(
+        round_sources,
+        vec![
+            "R1: MR(6) (6)",
+            "R2-R3: BLO, 6 sc",
+            "R2-R3: BLO, 6 sc",
+            "R4: mark(a), 6 inc (12)",
+        ]
+    );
+    let counts: Vec<Option<usize>> = part.rounds.iter().map(|r| r.declared_count).collect();
+    assert_eq!(counts, vec![Some(6), None, None, Some(12)]);
+
+    let actions = |i: usize| -> Vec<Action> {
+        part.round_actions(&part.rounds[i])
+            .iter()
+            .map(|a| a.action.clone())
+            .collect()
+    };
+    assert_eq!(
+        actions(0),
+        vec![
+            MR(6),
+            MarkRingStitch("anonymous_part.R1.s1".into(), 1),
+            EnforceAnchors(6, (1, 12))
+        ]
+    );
+    assert_eq!(actions(1), vec![BLO, Sc, Sc, Sc, Sc, Sc, Sc]);
+    assert_eq!(actions(2), vec![BLO, Sc, Sc, Sc, Sc, Sc, Sc]);
+    assert_eq!(actions(3)[..2], [BL, Mark("a".into())]);
+    assert_eq!(actions(3).last(), Some(&EnforceAnchors(12, (3, 21))));
+
+    let flat: Vec<&ActionWithOrigin> = part
+        .rounds
+        .iter()
+        .flat_map(|r| part.round_actions(r))
+        .collect();
+    assert_eq!(
+        flat.len(),
+        part.actions.len() - 3,
+        "everything except BeginPart, Sew and EndPart"
+    );
+}
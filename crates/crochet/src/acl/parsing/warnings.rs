@@ -0,0 +1,32 @@
+use std::fmt::Display;
+
+use crate::acl::Origin;
+
+/// Problem in the pattern that does not prevent building the plushie.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub code: WarningCode,
+    pub origin: Origin,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WarningCode {
+    /// Round label skips some numbers (e.g. "R5:" right after "R3:"), or the first round is not R1.
+    RoundNumberGap { expected: usize, found: usize },
+    /// Round label reuses numbers of previous rounds (e.g. "R4:" twice, or "R3-R4:" after "R5:").
+    RoundNumberReused { expected: usize, found: usize },
+    /// Parameter that has no effect on the part, e.g. "@firmness" without "@stuffing = pressure".
+    IgnoredParameter(String),
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} at bytes: [{}..{}]",
+            self.code,
+            self.origin.as_range().start,
+            self.origin.as_range().end
+        )
+    }
+}
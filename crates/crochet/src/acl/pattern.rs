@@ -1,12 +1,17 @@
-use std::{collections::HashMap, ops::Range};
+use std::{collections::HashMap, fmt::Display, ops::Range};
 
 use pest::Span;
 
-use crate::{ColorRgb, acl::Flow};
+use crate::{
+    ColorRgb,
+    acl::{Flow, PatternWarning},
+};
 
 #[derive(Debug, Clone)]
 pub struct PatternAst {
     pub parts: Vec<Part>,
+    /// Problems found while parsing that do not prevent building the plushie
+    pub warnings: Vec<PatternWarning>,
 }
 
 #[derive(Debug, Clone)]
@@ -15,14 +20,69 @@ pub struct Part {
     pub instances: usize,
     pub actions: Vec<ActionWithOrigin>,
     pub parameters: PartParameters,
+    /// Rounds in the order they are worked. Round repeated with "3:" or "R2-R4:" is listed once for every repetition.
+    pub rounds: Vec<Round>,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Round {
+    /// Number of the round within its part, as written in the label or counted from the previous round.
+    pub number: usize,
+    /// Location of the label (e.g. "R4" or "R7-R8"), if the round has one.
+    pub label: Option<Origin>,
+    /// Location of the whole round, from the label to the declared count.
+    pub origin: Origin,
+    /// Number of stitches the round should end with, e.g. "(12)".
+    pub declared_count: Option<usize>,
+    /// Range of `Part::actions` produced by this round.
+    pub actions: Range<usize>,
+}
+
+impl Part {
+    pub fn round_actions(&self, round: &Round) -> &[ActionWithOrigin] {
+        &self.actions[round.actions.clone()]
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct PartParameters {
     pub centroids: usize,
+    /// Set with "@centroids = auto", the number of centroids follows the size and shape of the part
+    pub auto_centroids: bool,
+    /// Set with "@handedness = left", parts without it take the handedness set before the first part, right by default
+    pub handedness: Handedness,
+    /// Set with "@inside_out = yes", the part is turned inside out once finished
+    pub inside_out: bool,
+    /// Set with "@stuffing = pressure" and "@firmness = 0.5"
+    pub stuffing: Stuffing,
+    /// Set with "@pellets = 2", weight added to every stitch of the part, a stitch of plain fabric weighs 1
+    pub pellets: f32,
     pub other: HashMap<String, String>,
 }
 
+/// Direction of working around the rounds. Left-handed work is a mirror image of right-handed work.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Handedness {
+    #[default]
+    Right,
+    Left,
+}
+
+/// How the simulation keeps the part stuffed
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Stuffing {
+    /// Centroids requested with "@centroids" push the nodes away
+    #[default]
+    Centroids,
+    /// The surface of the part is pushed out along its normals, as if the part was inflated.
+    /// Doesn't need tuning to the size of the part, but works only for closed parts.
+    Pressure { firmness: f32 },
+}
+
+impl Stuffing {
+    pub const DEFAULT_FIRMNESS: f32 = 0.5;
+}
+
 pub struct PatternIter<'p> {
     pub pattern: &'p PatternAst,
     pub action_cursor: usize,
@@ -117,6 +177,42 @@ pub struct ActionWithOrigin {
     pub action: Action,
     /// Location in the input string that produced this action.
     pub origin: Option<Origin>,
+    /// Which copy of the origin this is, when the origin was expanded by repetitions.
+    pub path: RepetitionPath,
+}
+
+/// Position of an action within its round and repetitions, e.g. "R5, repeat 4 of 6, 2nd of 3 sc".
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RepetitionPath {
+    /// Round the action was worked in. None for actions outside rounds.
+    pub round: Option<usize>,
+    /// Repetitions containing the action, outermost first. Includes "[...] x 6" as well as "3 sc".
+    pub repeats: Vec<Repeat>,
+    /// Stitch number within the round, counted like in [`StitchAddress`](crate::acl::StitchAddress). None for non-stitches.
+    pub stitch: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Repeat {
+    /// Starting from 1
+    pub index: usize,
+    pub of: usize,
+}
+
+impl Display for RepetitionPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut segments = vec![];
+        if let Some(round) = self.round {
+            segments.push(format!("R{round}"));
+        }
+        for repeat in &self.repeats {
+            segments.push(format!("repeat {} of {}", repeat.index, repeat.of));
+        }
+        if let Some(stitch) = self.stitch {
+            segments.push(format!("stitch {stitch}"));
+        }
+        write!(f, "{}", segments.join(", "))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -142,6 +238,12 @@ pub enum Action {
     /// Mark a spot that will be important later.
     /// Mark preceding an attach will point to the round that was left behind.
     Mark(Label),
+    /// Mark the given stitch of the magic ring made right before, starting from 1.
+    /// Placed after a magic ring by a [`StitchAddress`](crate::acl::StitchAddress) pointing into it.
+    MarkRingStitch(Label, usize),
+    /// Mark the first of the two stitches of the increase made right before.
+    /// Placed after an increase by a [`StitchAddress`](crate::acl::StitchAddress) pointing into it.
+    MarkFirstOfInc(Label),
     /// Magic ring
     MR(usize),
     /// Fasten off
@@ -161,6 +263,7 @@ impl Action {
         ActionWithOrigin {
             action: self,
             origin: Some(Origin::from_span(span)),
+            path: Default::default(),
         }
     }
 
@@ -168,6 +271,18 @@ impl Action {
         ActionWithOrigin {
             action: self,
             origin: None,
+            path: Default::default(),
+        }
+    }
+
+    /// Number of stitches worked into the fabric, that can be referenced with [`StitchAddress`](crate::acl::StitchAddress).
+    /// Every stitch of a magic ring counts, an increase makes two stitches.
+    pub(crate) fn stitch_count(&self) -> usize {
+        match self {
+            Action::MR(size) => *size,
+            Action::Inc => 2,
+            _ if self.is_repeatable() => 1,
+            _ => 0,
         }
     }
 
@@ -181,6 +296,8 @@ impl Action {
             | BL
             | Goto(_)
             | Mark(_)
+            | MarkRingStitch(_, _)
+            | MarkFirstOfInc(_)
             | MR(_)
             | FO
             | Color(_)
@@ -1,3 +1,8 @@
+use crate::{
+    acl::Action,
+    data::{Node, Peculiarity},
+};
+
 /// Edges guarantee that the underlying 2D vector that represents the yarn
 /// satisfies the condition that:
 /// for every `i` each element of `edges[i]` is smaller than `i`
@@ -108,17 +113,120 @@ impl PartialEq for Edges {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DeferredEdge {
     pub with_node: usize,
     pub node_a: usize,
     pub node_b: usize,
 }
 
+/// What a link stands for in the fabric, each kind has its own rest length and stiffness
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// Yarn between consecutive stitches of a round
+    Round,
+    /// Stitch to the stitch it was worked into
+    Anchor,
+    /// Anchor of a stitch worked into the front or back loop only
+    SingleLoopAnchor,
+    /// Link added by sew
+    Sew,
+    /// Chain stitches of an attach
+    Chain,
+    /// Stitch of a magic ring, or of a fastened off round, to its center
+    Spoke,
+}
+
+impl EdgeKind {
+    /// Kind of the link from node `i` to an earlier node `j`, links added by sew are not recognized.
+    pub(crate) fn of(nodes: &[Node], i: usize, j: usize) -> Self {
+        let node = &nodes[i];
+        match (&node.origin.action, &nodes[j].origin.action) {
+            (Action::MR(_), Action::MR(_)) if node.parent == Some(j) => EdgeKind::Spoke,
+            _ if node.peculiarity == Some(Peculiarity::Tip) => EdgeKind::Spoke,
+            (Action::Attach(..), _) => EdgeKind::Chain,
+            _ if j + 1 == i => EdgeKind::Round,
+            _ => match node.peculiarity {
+                Some(Peculiarity::BLO(_) | Peculiarity::FLO(_)) => EdgeKind::SingleLoopAnchor,
+                _ => EdgeKind::Anchor,
+            },
+        }
+    }
+
+    /// Kinds of all `edges`, mirroring their structure. `sewn` were linked after the edges built by the hook.
+    pub(crate) fn of_all(nodes: &[Node], edges: &Edges, sewn: &[DeferredEdge]) -> Vec<Vec<Self>> {
+        let mut sewn: Vec<(usize, usize)> = sewn
+            .iter()
+            .map(|edge| (edge.node_a.max(edge.node_b), edge.node_a.min(edge.node_b)))
+            .collect();
+        edges
+            .iter()
+            .enumerate()
+            .map(|(i, targets)| {
+                // sews are pushed after the links of the node itself
+                let mut kinds: Vec<Self> = targets
+                    .iter()
+                    .rev()
+                    .map(|j| match sewn.iter().position(|edge| *edge == (i, *j)) {
+                        Some(position) => {
+                            sewn.swap_remove(position);
+                            EdgeKind::Sew
+                        }
+                        None => Self::of(nodes, i, *j),
+                    })
+                    .collect();
+                kinds.reverse();
+                kinds
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn count_kinds(pattern: &str) -> std::collections::HashMap<EdgeKind, usize> {
+        let (definition, _) = crate::build_definition(pattern).unwrap();
+        let mut edges = definition.edges.clone();
+        for edge in &definition.deferred_edges {
+            edges.link(edge.node_a, edge.node_b);
+        }
+        let kinds = EdgeKind::of_all(&definition.nodes, &edges, &definition.deferred_edges);
+        let mut counts = std::collections::HashMap::new();
+        for (targets, kinds) in edges.iter().zip(&kinds) {
+            assert_eq!(targets.len(), kinds.len());
+            for kind in kinds {
+                *counts.entry(*kind).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn test_edge_kinds() {
+        let counts = count_kinds(indoc::indoc! {"
+            == Body ==
+            : MR(6)
+            : BLO, 6 inc (12)
+            : 12 sc (12)
+
+            == Eye ==
+            : MR(6)
+            : 6 sc (6)
+            FO
+
+            sew(Body.R3.s1, Eye.R2.s1)
+        "});
+        // 6 for every magic ring, and the eye closed with FO
+        assert_eq!(counts[&EdgeKind::Spoke], 12 + 6);
+        assert_eq!(counts[&EdgeKind::SingleLoopAnchor], 12);
+        assert_eq!(counts[&EdgeKind::Sew], 1);
+        assert_eq!(counts.get(&EdgeKind::Chain), None);
+        // every stitch but the first of a part links to the previous one
+        assert_eq!(counts[&EdgeKind::Round], 5 + 12 + 12 + 5 + 6);
+    }
+
     #[test]
     fn test_edges_from_reorders_indexes() {
         let src = vec![
@@ -73,6 +73,15 @@ impl PartClusters {
         }
     }
 
+    /// Number of joins performed so far, replaying that many joins restores the clusters
+    pub(crate) fn performed_joins(&self) -> usize {
+        self.cursor
+    }
+
+    pub(crate) fn joins(&self) -> &[PartJoin] {
+        &self.joins
+    }
+
     pub fn get_part_cluster(&self, part: usize) -> usize {
         assert!(part < self.part_to_cluster.len());
         self.part_to_cluster[part]
@@ -174,6 +183,7 @@ mod tests {
         let hook_params = HookParams {
             tip_from_fo: true,
             enforce_counts: false,
+            ..Default::default()
         };
         let graph = graph_construction::parse(pattern.as_iter(), hook_params).unwrap();
         assert_eq!(graph.part_joins.part_to_cluster.len(), 3);
@@ -224,6 +234,7 @@ mod tests {
         let hook_params = HookParams {
             tip_from_fo: true,
             enforce_counts: false,
+            ..Default::default()
         };
         let graph = graph_construction::parse(pattern.as_iter(), hook_params).unwrap();
         assert_eq!(graph.part_joins.part_to_cluster.len(), 3);
@@ -281,6 +292,7 @@ mod tests {
         let hook_params = HookParams {
             tip_from_fo: true,
             enforce_counts: false,
+            ..Default::default()
         };
         let graph = graph_construction::parse(pattern.as_iter(), hook_params).unwrap();
         let mut joins = graph.part_joins;
@@ -1,7 +1,22 @@
-use crate::{ColorRgb, acl::ActionWithOrigin};
+use crate::{
+    ColorRgb,
+    acl::{ActionWithOrigin, RepetitionPath},
+};
 
 pub type NodeIndex = usize;
 
+/// Identifies a node independently of its index, so it survives edits elsewhere in the pattern.
+/// Inserting a stitch changes ids of the following stitches in the same round,
+/// changing a repetition (e.g. "12 sc" to "11 sc") changes ids of all stitches in it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    pub part: String,
+    /// Round, repetitions and stitch number of the action that created the node
+    pub path: RepetitionPath,
+    /// Tells apart nodes created by the same stitch (e.g. inc or MR) or outside of rounds (e.g. FO). Starting from 0.
+    pub index: usize,
+}
+
 #[derive(Clone, Debug)]
 pub struct Node {
     pub color: ColorRgb,
@@ -11,6 +26,21 @@ pub struct Node {
     /// Anchor of this node. Used for single loop forces.
     pub(crate) parent: Option<NodeIndex>,
     pub part_index: usize,
+    /// The part is turned inside out once finished, so its single loop ridges end up on the other side
+    pub inside_out: bool,
+    /// Assigned once the whole graph is built, see [`PlushieDef::assign_node_ids`](crate::PlushieDef).
+    pub id: NodeId,
+}
+
+impl Node {
+    /// Peculiarity as seen from the outside of the finished part
+    pub fn outside_peculiarity(&self) -> Option<Peculiarity> {
+        match self.peculiarity {
+            Some(Peculiarity::BLO(points)) if self.inside_out => Some(Peculiarity::FLO(points)),
+            Some(Peculiarity::FLO(points)) if self.inside_out => Some(Peculiarity::BLO(points)),
+            peculiarity => peculiarity,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
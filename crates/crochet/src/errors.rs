@@ -1,6 +1,8 @@
 use crate::{
     acl::{Origin, PatternError},
+    force_graph::simulated_plushie::snapshot::SnapshotError,
     graph_construction::HookError,
+    validation::Error as ValidationError,
 };
 use std::fmt::Display;
 
@@ -8,6 +10,10 @@ use std::fmt::Display;
 pub enum Error {
     Pattern(PatternError),
     Hook(HookError),
+    /// Pattern produced a graph that breaks the invariants expected by the simulation
+    Invalid(ValidationError),
+    /// Snapshot can't be resumed with this pattern
+    Snapshot(SnapshotError),
 }
 
 impl Error {
@@ -15,6 +21,8 @@ impl Error {
         match self {
             Error::Pattern(error) => Some(error.origin),
             Error::Hook(hook_error_with_origin) => hook_error_with_origin.origin,
+            Error::Invalid(validation_error) => validation_error.origin,
+            Error::Snapshot(_) => None,
         }
     }
 }
@@ -32,6 +40,8 @@ impl Display for Error {
                     ""
                 }
             ),
+            Error::Invalid(e) => write!(f, "invalid graph, please report this: {e}"),
+            Error::Snapshot(e) => write!(f, "{e}"),
         }
     }
 }
@@ -1,22 +1,89 @@
-use std::ops::Not;
+use std::ops::{Not, Range};
 
 use glam::Vec3;
 
-use crate::force_graph::simulated_plushie::Node;
+use crate::{data::Node as NodeDefinition, force_graph::simulated_plushie::Node};
 
+/// Pushes nodes of a part away from its centroids, then moves the centroids to the middle of the nodes closest to them.
 pub fn centroid_stuffing(
     nodes: &[Node],
-    centroids: &mut Vec<Vec3>,
+    centroids: &mut [Vec3],
     hook_size: f32,
     displacement: &mut [Vec3],
 ) {
     assert_eq!(nodes.len(), displacement.len());
-    let centroid_force = 0.05;
     let centroid_to_points =
-        push_and_map(&nodes, &centroids, centroid_force, displacement, hook_size);
-    recalculate_centroids(&nodes, centroids, centroid_to_points, hook_size);
+        push_and_map(nodes, centroids, CENTROID_FORCE, displacement, hook_size);
+    recalculate_centroids(nodes, centroids, centroid_to_points, hook_size);
+}
+
+/// Pushes nodes away from centroids of another part. The nodes don't affect positions of the centroids.
+pub fn centroid_push(
+    nodes: &[Node],
+    centroids: &[Vec3],
+    hook_size: f32,
+    displacement: &mut [Vec3],
+) {
+    assert_eq!(nodes.len(), displacement.len());
+    if centroids.is_empty() {
+        return;
+    }
+    for (node, displacement) in nodes.iter().zip(displacement) {
+        push_node(node, centroids, CENTROID_FORCE, displacement, hook_size);
+    }
 }
 
+/// Same as [`centroid_stuffing`], gives exactly the same results.
+#[cfg(feature = "parallel")]
+pub fn centroid_stuffing_parallel(
+    nodes: &[Node],
+    centroids: &mut [Vec3],
+    hook_size: f32,
+    displacement: &mut [Vec3],
+) {
+    use rayon::prelude::*;
+
+    assert_eq!(nodes.len(), displacement.len());
+    let mut centroid_to_points = vec![vec![]; centroids.len()];
+    if !centroids.is_empty() {
+        let closest: Vec<usize> = nodes
+            .par_iter()
+            .zip(displacement)
+            .map(|(node, displacement)| {
+                push_node(node, centroids, CENTROID_FORCE, displacement, hook_size)
+            })
+            .collect();
+        for (node_index, closest) in closest.into_iter().enumerate() {
+            centroid_to_points[closest].push(node_index);
+        }
+    }
+    recalculate_centroids(nodes, centroids, centroid_to_points, hook_size);
+}
+
+/// Same as [`centroid_push`], gives exactly the same results.
+#[cfg(feature = "parallel")]
+pub fn centroid_push_parallel(
+    nodes: &[Node],
+    centroids: &[Vec3],
+    hook_size: f32,
+    displacement: &mut [Vec3],
+) {
+    use rayon::prelude::*;
+
+    assert_eq!(nodes.len(), displacement.len());
+    if centroids.is_empty() {
+        return;
+    }
+    nodes
+        .par_iter()
+        .zip(displacement)
+        .for_each(|(node, displacement)| {
+            push_node(node, centroids, CENTROID_FORCE, displacement, hook_size);
+        });
+}
+
+const CENTROID_FORCE: f32 = 0.05;
+
 fn push_and_map(
     nodes: &[Node],
     centroids: &[Vec3],
@@ -30,21 +97,38 @@ fn push_and_map(
     }
 
     for (node_index, node) in nodes.iter().enumerate() {
-        let mut closest_centroid_index = 0;
-        let mut closest = distance(node.position, centroids[closest_centroid_index]);
-        for (centroid_index, centroid) in centroids.iter().enumerate() {
-            if distance(node.position, *centroid) < closest {
-                closest = distance(node.position, *centroid);
-                closest_centroid_index = centroid_index;
-            }
-            displacement[node_index] +=
-                push_away(&node.position, centroid, hook_size) * centroid_force;
-        }
+        let closest_centroid_index = push_node(
+            node,
+            centroids,
+            centroid_force,
+            &mut displacement[node_index],
+            hook_size,
+        );
         centroid_to_points[closest_centroid_index].push(node_index);
     }
     centroid_to_points
 }
 
+/// Pushes the node away from all centroids, returns index of the closest centroid
+fn push_node(
+    node: &Node,
+    centroids: &[Vec3],
+    centroid_force: f32,
+    displacement: &mut Vec3,
+    hook_size: f32,
+) -> usize {
+    let mut closest_centroid_index = 0;
+    let mut closest = distance(node.position, centroids[closest_centroid_index]);
+    for (centroid_index, centroid) in centroids.iter().enumerate() {
+        if distance(node.position, *centroid) < closest {
+            closest = distance(node.position, *centroid);
+            closest_centroid_index = centroid_index;
+        }
+        *displacement += push_away(&node.position, centroid, hook_size) * centroid_force;
+    }
+    closest_centroid_index
+}
+
 fn recalculate_centroids(
     nodes: &[Node],
     centroids: &mut [Vec3],
@@ -92,6 +176,142 @@ fn recalculate_centroids(
     // sanity!(centroids.assert_no_nan("after recalculating centroids"));
 }
 
+/// Round of every node of the part, counted along the anchors from the start of the part.
+/// Nodes that aren't anchored in the part, like the magic ring root or chains, are in round 0.
+pub(crate) fn node_rounds(nodes: &[NodeDefinition], part: Range<usize>) -> Vec<usize> {
+    let mut rounds: Vec<usize> = Vec::with_capacity(part.len());
+    for node in &nodes[part.clone()] {
+        let round = match node.parent {
+            Some(parent) if part.contains(&parent) => rounds[parent - part.start] + 1,
+            _ => 0,
+        };
+        rounds.push(round);
+    }
+    rounds
+}
+
+/// Middle of a round and its mean distance from the nodes of the round
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisPoint {
+    pub position: Vec3,
+    pub radius: f32,
+}
+
+/// Centers of the rounds, in order. They follow the middle of the part, like a spine.
+/// Rounds of fewer than 3 nodes can't be told apart from the surface and are left out.
+pub fn medial_axis(nodes: &[Node], rounds: &[usize]) -> Vec<AxisPoint> {
+    let round_count = rounds
+        .iter()
+        .take(nodes.len())
+        .max()
+        .map_or(0, |max| max + 1);
+    let mut sums = vec![(Vec3::ZERO, 0); round_count];
+    for (node, &round) in nodes.iter().zip(rounds) {
+        sums[round].0 += node.position;
+        sums[round].1 += 1;
+    }
+    let centers: Vec<Option<Vec3>> = sums
+        .iter()
+        .map(|&(sum, count)| (count >= 3).then(|| sum / count as f32))
+        .collect();
+    let mut radii = vec![0.0; round_count];
+    for (node, &round) in nodes.iter().zip(rounds) {
+        if let Some(center) = centers[round] {
+            radii[round] += node.position.distance(center) / sums[round].1 as f32;
+        }
+    }
+    centers
+        .into_iter()
+        .zip(radii)
+        .filter_map(|(center, radius)| center.map(|position| AxisPoint { position, radius }))
+        .collect()
+}
+
+/// Upper limit of [`auto_centroid_count`], in case the part goes haywire
+pub const MAX_AUTO_CENTROIDS: usize = 32;
+
+/// Roughly one centroid per diameter of the part along its axis, at least one.
+/// Segments of the axis count by their radius against the widest round, so the narrow rounds closing a ball don't add centroids of their own.
+/// Stays at `current` unless the shape calls for a different count by a clear margin, so that centroids don't flicker in and out.
+pub fn auto_centroid_count(axis: &[AxisPoint], current: usize, hook_size: f32) -> usize {
+    if axis.is_empty() {
+        return current;
+    }
+    let widest = axis
+        .iter()
+        .map(|point| point.radius)
+        .fold(hook_size, f32::max);
+    let wanted: f32 = axis
+        .windows(2)
+        .map(|pair| {
+            let radius = ((pair[0].radius + pair[1].radius) / 2.0).max(hook_size);
+            let weight = radius / widest;
+            weight * pair[0].position.distance(pair[1].position) / (2.0 * radius)
+        })
+        .sum::<f32>()
+        .max(1.0);
+    if (wanted - current as f32).abs() < 0.75 {
+        current
+    } else {
+        (wanted.round() as usize).clamp(1, MAX_AUTO_CENTROIDS)
+    }
+}
+
+/// Adds or removes centroids to get to `wanted`.
+/// New centroids are placed at the point of the axis furthest from the other centroids, or around the middle of the nodes without an axis.
+/// Removed centroids are merged with their closest neighbor.
+pub fn resize_centroids(
+    centroids: &mut Vec<Vec3>,
+    wanted: usize,
+    nodes: &[Node],
+    axis: &[AxisPoint],
+    hook_size: f32,
+) {
+    while centroids.len() < wanted {
+        let seed = axis
+            .iter()
+            .map(|point| point.position)
+            .max_by(|a, b| {
+                distance_to_closest(*a, centroids).total_cmp(&distance_to_closest(*b, centroids))
+            })
+            .unwrap_or_else(|| {
+                nodes.iter().map(|node| node.position).sum::<Vec3>() / nodes.len().max(1) as f32
+            });
+        // two centroids at the same spot would never separate
+        let seed = if centroids.contains(&seed) {
+            seed + Vec3::new(hook_size * centroids.len() as f32, 0.0, 0.0)
+        } else {
+            seed
+        };
+        centroids.push(seed);
+    }
+    while centroids.len() > wanted {
+        let mut closest = (0, 1, f32::INFINITY);
+        for i in 0..centroids.len() {
+            for j in i + 1..centroids.len() {
+                let distance = distance(centroids[i], centroids[j]);
+                if distance < closest.2 {
+                    closest = (i, j, distance);
+                }
+            }
+        }
+        let (i, j, _) = closest;
+        if j >= centroids.len() {
+            centroids.clear();
+            break;
+        }
+        centroids[i] = (centroids[i] + centroids[j]) / 2.0;
+        centroids.remove(j);
+    }
+}
+
+fn distance_to_closest(point: Vec3, centroids: &[Vec3]) -> f32 {
+    centroids
+        .iter()
+        .map(|centroid| distance(point, *centroid))
+        .fold(f32::INFINITY, f32::min)
+}
+
 fn distance(a: Vec3, b: Vec3) -> f32 {
     a.distance(b)
 }
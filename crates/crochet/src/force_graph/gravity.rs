@@ -0,0 +1,166 @@
+use glam::{Vec2, Vec3, Vec3Swizzles};
+
+use crate::force_graph::simulated_plushie::{Node, Part};
+
+/// Pull on every node of a part without pellets, a fully stretched link pulls with 1.
+/// Gravity points along -Y, the magic ring starts at the origin and the plushie grows up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gravity {
+    pub strength: f32,
+}
+
+/// Pulls the nodes down, nodes of parts filled with pellets are heavier.
+pub(crate) fn gravity_forces(
+    nodes: &[Node],
+    parts: &[Part],
+    gravity: &Gravity,
+    displacement: &mut [Vec3],
+) {
+    for (node, displacement) in nodes.iter().zip(displacement) {
+        displacement.y -= gravity.strength * mass(node, parts);
+    }
+}
+
+/// 1 for plain fabric, heavier with pellets
+fn mass(node: &Node, parts: &[Part]) -> f32 {
+    1.0 + parts
+        .get(node.definition.part_index)
+        .map_or(0.0, |part| part.pellets)
+}
+
+/// Whether a plushie lying on the floor stays upright
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Balance {
+    /// No node touches the floor
+    Airborne,
+    /// Center of mass is above the area the plushie stands on.
+    /// `margin` is how far it could move before leaving that area.
+    Stable { margin: f32 },
+    /// Center of mass is outside of the area the plushie stands on, it falls over towards `direction`.
+    TipsOver { direction: Vec3 },
+}
+
+/// Nodes closer to the floor than this fraction of `hook_size` are standing on it
+const CONTACT_DISTANCE: f32 = 0.5;
+
+/// Compares the center of mass with the outline of the nodes touching the floor, as seen from above.
+pub fn balance(nodes: &[Node], parts: &[Part], floor: f32, hook_size: f32) -> Balance {
+    let contacts: Vec<Vec2> = nodes
+        .iter()
+        .filter(|node| node.position.y <= floor + CONTACT_DISTANCE * hook_size)
+        .map(|node| node.position.xz())
+        .collect();
+    if contacts.is_empty() {
+        return Balance::Airborne;
+    }
+    let (weighted_sum, total_mass) = nodes.iter().fold((Vec3::ZERO, 0.0), |(sum, total), node| {
+        let mass = mass(node, parts);
+        (sum + node.position * mass, total + mass)
+    });
+    let center_of_mass = (weighted_sum / total_mass).xz();
+
+    let outline = convex_hull(contacts);
+    let closest = closest_point_of_outline(&outline, center_of_mass);
+    let inside = outline.len() >= 3
+        && outline
+            .iter()
+            .zip(outline.iter().cycle().skip(1))
+            .all(|(a, b)| (*b - *a).perp_dot(center_of_mass - *a) >= 0.0);
+    if inside {
+        Balance::Stable {
+            margin: closest.distance(center_of_mass),
+        }
+    } else {
+        let direction = (center_of_mass - closest).normalize_or_zero();
+        Balance::TipsOver {
+            direction: Vec3::new(direction.x, 0.0, direction.y),
+        }
+    }
+}
+
+/// Counterclockwise, without repeating the first point (Andrew's monotone chain)
+fn convex_hull(mut points: Vec<Vec2>) -> Vec<Vec2> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let mut hull: Vec<Vec2> = Vec::with_capacity(points.len() + 1);
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for point in pass {
+            while hull.len() >= start + 2
+                && (hull[hull.len() - 1] - hull[hull.len() - 2])
+                    .perp_dot(point - hull[hull.len() - 2])
+                    <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // the last point of each pass is the first of the other
+        hull.pop();
+    }
+    hull
+}
+
+fn closest_point_of_outline(outline: &[Vec2], point: Vec2) -> Vec2 {
+    if outline.len() == 1 {
+        return outline[0];
+    }
+    outline
+        .iter()
+        .zip(outline.iter().cycle().skip(1))
+        .map(|(a, b)| {
+            let edge = *b - *a;
+            let t = ((point - *a).dot(edge) / edge.length_squared()).clamp(0.0, 1.0);
+            *a + edge * t
+        })
+        .min_by(|a, b| a.distance(point).total_cmp(&b.distance(point)))
+        .unwrap_or(point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_convex_hull() {
+        let points = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(2.0, 0.0),
+        ];
+        assert_eq!(
+            convex_hull(points),
+            vec![
+                Vec2::new(0.0, 0.0),
+                Vec2::new(2.0, 0.0),
+                Vec2::new(2.0, 2.0),
+                Vec2::new(0.0, 2.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_closest_point_of_outline() {
+        let square = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+        ];
+        assert_eq!(
+            closest_point_of_outline(&square, Vec2::new(1.0, 0.5)),
+            Vec2::new(1.0, 0.0)
+        );
+        assert_eq!(
+            closest_point_of_outline(&square, Vec2::new(3.0, 3.0)),
+            Vec2::new(2.0, 2.0)
+        );
+    }
+}
@@ -1,23 +1,40 @@
-use std::f32::consts::PI;
+use std::{f32::consts::PI, ops::Range};
 
 use glam::Vec3;
 
+use crate::{
+    acl::Handedness,
+    data::{Node as NodeDefinition, Peculiarity},
+    force_graph::centroid_stuffing::node_rounds,
+};
+
 #[derive(PartialEq)]
 pub enum Initializer {
     /// Start with nodes arranged into a cylinder shape. Advance height every X nodes.
     /// Magic Ring is placed at origin.
     RegularCylinder(u32),
     /// Spawn the nodes one by one, waiting for the previous node to reach a relatively stable position before advancing.
+    /// The pace is set by [`OneByOneParams`](super::simulated_plushie::init::OneByOneParams).
     OneByOne,
+    /// Every round is a ring as long as its stitch count, stacked one hook size above the round it is worked into.
+    /// Starts close to the finished shape of parts worked in the round. Parts are placed side by side.
+    Lathe,
 }
 
 impl Initializer {
-    pub fn apply(&self, nodes_num: u32, hook_size: f32) -> Vec<Vec3> {
+    /// Starting positions of `nodes`, `part_limits` are the ends of the parts.
+    pub fn apply(
+        &self,
+        nodes: &[NodeDefinition],
+        part_limits: &[usize],
+        hook_size: f32,
+    ) -> Vec<Vec3> {
         match self {
             Initializer::RegularCylinder(nodes_in_cirumference) => {
-                arrange_cylinder(nodes_num, *nodes_in_cirumference, hook_size)
+                arrange_cylinder(nodes.len() as u32, *nodes_in_cirumference, hook_size)
             }
             Initializer::OneByOne => vec![],
+            Initializer::Lathe => arrange_lathe(nodes, part_limits, hook_size),
         }
     }
 }
@@ -27,18 +44,140 @@ fn arrange_cylinder(nodes_num: u32, nodes_in_cirumference: u32, hook_size: f32)
         return vec![];
     }
 
+    // ring of 0 would never advance
+    let nodes_in_cirumference = nodes_in_cirumference.max(1);
     let mut y = 0.0;
     let mut nodes = vec![Vec3::ZERO]; // for the magic ring
 
     while nodes.len() as u32 + nodes_in_cirumference < nodes_num {
-        nodes.append(&mut ring(nodes_in_cirumference, y, hook_size));
+        nodes.append(&mut ring(
+            nodes_in_cirumference,
+            y,
+            hook_size,
+            Handedness::Right,
+        ));
         y += hook_size;
     }
-    nodes.append(&mut ring(nodes_num - nodes.len() as u32, y, hook_size));
+    nodes.append(&mut ring(
+        nodes_num - nodes.len() as u32,
+        y,
+        hook_size,
+        Handedness::Right,
+    ));
     nodes
 }
 
-pub fn ring(members: u32, y: f32, hook_size: f32) -> Vec<Vec3> {
+/// Gap between the widest rounds of neighboring parts, in hook sizes
+const PART_SPACING: f32 = 2.0;
+
+/// The first part has its magic ring at the origin, the following parts are placed along +X
+fn arrange_lathe(nodes: &[NodeDefinition], part_limits: &[usize], hook_size: f32) -> Vec<Vec3> {
+    let mut positions = Vec::with_capacity(nodes.len());
+    let mut start = 0;
+    let mut right_edge: Option<f32> = None;
+    for &end in part_limits {
+        let rings = lathe_rings(nodes, start..end, hook_size);
+        let widest = rings.iter().map(|ring| ring.radius).fold(0.0, f32::max);
+        let x = right_edge.map_or(0.0, |edge| edge + PART_SPACING * hook_size + widest);
+        right_edge = Some(x + widest);
+        for ring in &rings {
+            let angular_interval = 2.0 * PI / ring.len as f32;
+            for i in 0..ring.len {
+                let rads = ring.phase + angular_interval * i as f32;
+                positions.push(Vec3::new(
+                    x + rads.cos() * ring.radius,
+                    ring.y,
+                    rads.sin() * ring.radius,
+                ));
+            }
+        }
+        start = end;
+    }
+    positions
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LatheRing {
+    /// Index of the first node, relative to the start of the part
+    start: usize,
+    len: usize,
+    y: f32,
+    radius: f32,
+    /// +1 while the work grows up, -1 after a front loop round folded it back down
+    direction: f32,
+    /// Angle of the first node
+    phase: f32,
+}
+
+/// Consecutive nodes of the same round form a ring.
+/// A round revisited later, e.g. after a goto, forms another ring.
+fn lathe_rings(nodes: &[NodeDefinition], part: Range<usize>, hook_size: f32) -> Vec<LatheRing> {
+    let rounds = node_rounds(nodes, part.clone());
+    let mut ring_of_node = vec![0; rounds.len()];
+    let mut rings: Vec<LatheRing> = vec![];
+    let mut start = 0;
+    while start < rounds.len() {
+        let len = rounds[start..]
+            .iter()
+            .take_while(|round| **round == rounds[start])
+            .count();
+        let radius = match len {
+            1 => 0.0,
+            _ => len as f32 * hook_size / (2.0 * PI),
+        };
+        let first = &nodes[part.start + start];
+        let base_node = match first.peculiarity {
+            // fasten off closes the last round
+            Some(Peculiarity::Tip) if start > 0 => Some(part.start + start - 1),
+            _ => first.parent.filter(|parent| part.contains(parent)),
+        };
+        let ring = match base_node {
+            Some(base_node) => {
+                let base = rings[ring_of_node[base_node - part.start]];
+                let direction = match first.peculiarity {
+                    Some(Peculiarity::FLO(_)) => -base.direction,
+                    _ => base.direction,
+                };
+                // one hook size away from the base along the fabric, rounds that only widen lie flat
+                let rise = (hook_size.powi(2) - (radius - base.radius).powi(2))
+                    .max(0.0)
+                    .sqrt();
+                LatheRing {
+                    start,
+                    len,
+                    y: base.y + direction * rise,
+                    radius,
+                    direction,
+                    // start above the anchor, so the links to the base stay short
+                    phase: base.phase
+                        + 2.0 * PI * (base_node - part.start - base.start) as f32 / base.len as f32,
+                }
+            }
+            None => LatheRing {
+                start,
+                len,
+                y: 0.0,
+                radius,
+                direction: 1.0,
+                phase: 0.0,
+            },
+        };
+        ring_of_node[start..start + len].fill(rings.len());
+        rings.push(ring);
+        start += len;
+    }
+    rings
+}
+
+/// Left-handed work winds the other way around the rounds, so it is mirrored along Z
+pub(crate) fn wound(position: Vec3, handedness: Handedness) -> Vec3 {
+    match handedness {
+        Handedness::Right => position,
+        Handedness::Left => position * Vec3::new(1.0, 1.0, -1.0),
+    }
+}
+
+pub fn ring(members: u32, y: f32, hook_size: f32, handedness: Handedness) -> Vec<Vec3> {
     let circumference = hook_size * members as f32;
     let radius = circumference / (2.0 * PI);
 
@@ -50,7 +189,7 @@ pub fn ring(members: u32, y: f32, hook_size: f32) -> Vec<Vec3> {
         let x = rads.cos() * radius;
         let z = rads.sin() * radius;
         let point = Vec3::new(x, y, z);
-        result.push(point);
+        result.push(wound(point, handedness));
     }
     result
 }
@@ -72,5 +211,140 @@ mod tests {
 
         let res = arrange_cylinder(12, 12, 1.0);
         assert_eq!(res.len(), 12);
+
+        let res = arrange_cylinder(7, 0, 1.0);
+        assert_eq!(res.len(), 7);
+    }
+
+    mod lathe {
+        use super::*;
+        use indoc::indoc;
+
+        const BALL: &str = indoc! {"
+            : MR(6)
+            : 6 inc (12)
+            : [sc, inc] x 6 (18)
+            2: 18 sc (18)
+            : [sc, dec] x 6 (12)
+            : 6 dec (6)
+            FO
+        "};
+
+        fn positions(pattern: &str, initializer: Initializer) -> (crate::PlushieDef, Vec<Vec3>) {
+            let (definition, part_limits) = crate::build_definition(pattern).unwrap();
+            let positions = initializer.apply(&definition.nodes, &part_limits, 1.0);
+            (definition, positions)
+        }
+
+        /// Largest difference between a link length and the hook size
+        fn worst_link(definition: &crate::PlushieDef, positions: &[Vec3]) -> f32 {
+            let deferred = definition
+                .deferred_edges
+                .iter()
+                .map(|edge| (edge.node_a, edge.node_b));
+            definition
+                .edges
+                .iter()
+                .enumerate()
+                .flat_map(|(a, targets)| targets.iter().map(move |b| (a, *b)))
+                .chain(deferred)
+                .map(|(a, b)| (positions[a].distance(positions[b]) - 1.0).abs())
+                .fold(0.0, f32::max)
+        }
+
+        #[test]
+        fn test_rounds_are_as_wide_as_their_stitch_count() {
+            let (_, positions) = positions(BALL, Initializer::Lathe);
+            assert_eq!(positions[0], Vec3::ZERO);
+            // 6 inc round
+            for position in &positions[7..19] {
+                let radius = Vec3::new(position.x, 0.0, position.z).length();
+                assert!((radius - 12.0 / (2.0 * PI)).abs() < 1e-4, "{radius}");
+            }
+            let top = positions.last().unwrap().y;
+            assert!(top > 3.0, "{top}");
+        }
+
+        #[test]
+        fn test_links_start_closer_to_their_length_than_in_a_cylinder() {
+            let (definition, lathe) = positions(BALL, Initializer::Lathe);
+            let (_, cylinder) = positions(BALL, Initializer::RegularCylinder(12));
+            assert!(
+                worst_link(&definition, &lathe) < worst_link(&definition, &cylinder) / 2.0,
+                "{} {}",
+                worst_link(&definition, &lathe),
+                worst_link(&definition, &cylinder)
+            );
+        }
+
+        #[test]
+        fn test_front_loop_round_folds_the_work_down() {
+            let (definition, positions) = positions(
+                indoc! {"
+                : MR(6)
+                : 6 inc (12)
+                3: 12 sc (12)
+                mark(cap_start)
+                : BLO, 6 dec (6)
+                FO
+
+                goto(cap_start)
+                : FLO, 12 inc (24)
+                3: 24 sc (24)
+                "},
+                Initializer::Lathe,
+            );
+            let cap_start = definition
+                .nodes
+                .iter()
+                .position(|node| matches!(node.peculiarity, Some(Peculiarity::FLO(_))))
+                .unwrap();
+            let rim = positions[cap_start - 1].y;
+            assert!(positions[cap_start..].iter().all(|p| p.y <= rim + 1e-4));
+            assert!(positions.last().unwrap().y < rim - 2.0);
+        }
+
+        #[test]
+        fn test_parts_do_not_overlap() {
+            let (definition, positions) = positions(
+                indoc! {"
+                == Body ==
+                : MR(6)
+                : 6 inc (12)
+                2: 12 sc (12)
+
+                == Arm ==
+                : MR(6)
+                2: 6 sc (6)
+                "},
+                Initializer::Lathe,
+            );
+            let arm = definition
+                .nodes
+                .iter()
+                .position(|node| node.part_index == 1)
+                .unwrap();
+            let body_right = positions[..arm]
+                .iter()
+                .map(|p| p.x)
+                .fold(f32::MIN, f32::max);
+            let arm_left = positions[arm..]
+                .iter()
+                .map(|p| p.x)
+                .fold(f32::MAX, f32::min);
+            assert!(arm_left - body_right > 1.5, "{body_right} {arm_left}");
+        }
+    }
+
+    #[test]
+    fn test_left_ring_winds_the_other_way() {
+        let right = ring(6, 0.0, 1.0, Handedness::Right);
+        let left = ring(6, 0.0, 1.0, Handedness::Left);
+        for (right, left) in right.iter().zip(&left) {
+            assert_eq!(*left, Vec3::new(right.x, right.y, -right.z));
+        }
+        // seen from above, right-handed rounds go counterclockwise
+        assert!(right[1].z > 0.0);
+        assert!(left[1].z < 0.0);
     }
 }
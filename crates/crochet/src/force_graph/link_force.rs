@@ -1,30 +1,204 @@
 use glam::Vec3;
 
-use crate::{data::Edges, force_graph::simulated_plushie::Node};
+use crate::{
+    data::{EdgeKind, Edges},
+    force_graph::simulated_plushie::Node,
+};
+
+/// Rest length and stiffness of one kind of link
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkParams {
+    /// In hook sizes
+    pub length: f32,
+    /// Multiplies the tension of the link
+    pub stiffness: f32,
+}
+
+/// Links of every [`EdgeKind`], tuned to match how the real fabric stretches
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkGauge {
+    pub round: LinkParams,
+    pub anchor: LinkParams,
+    pub single_loop_anchor: LinkParams,
+    pub sew: LinkParams,
+    pub chain: LinkParams,
+    pub spoke: LinkParams,
+}
+
+impl LinkGauge {
+    const PLAIN: LinkParams = LinkParams {
+        length: 1.0,
+        stiffness: 1.0,
+    };
+
+    /// Every link is one hook size long and equally stiff
+    pub const UNIFORM: Self = Self {
+        round: Self::PLAIN,
+        anchor: Self::PLAIN,
+        single_loop_anchor: Self::PLAIN,
+        sew: Self::PLAIN,
+        chain: Self::PLAIN,
+        spoke: Self::PLAIN,
+    };
+
+    /// Rough proportions of single crochet: stitches are taller than wide, the fabric gives more along the rounds,
+    /// single loop stitches are looser and sewn stitches are pulled tight.
+    pub const FABRIC: Self = Self {
+        round: LinkParams {
+            length: 1.0,
+            stiffness: 0.6,
+        },
+        anchor: LinkParams {
+            length: 1.2,
+            stiffness: 1.0,
+        },
+        single_loop_anchor: LinkParams {
+            length: 1.4,
+            stiffness: 0.6,
+        },
+        sew: LinkParams {
+            length: 0.5,
+            stiffness: 1.0,
+        },
+        chain: Self::PLAIN,
+        spoke: Self::PLAIN,
+    };
+
+    pub fn link(&self, kind: EdgeKind) -> LinkParams {
+        match kind {
+            EdgeKind::Round => self.round,
+            EdgeKind::Anchor => self.anchor,
+            EdgeKind::SingleLoopAnchor => self.single_loop_anchor,
+            EdgeKind::Sew => self.sew,
+            EdgeKind::Chain => self.chain,
+            EdgeKind::Spoke => self.spoke,
+        }
+    }
+}
+
+impl Default for LinkGauge {
+    fn default() -> Self {
+        Self::UNIFORM
+    }
+}
 
 /// O(N) assuming close-to-constant edge count in each node
 pub(crate) fn link_forces(
     nodes: &[Node],
     edges: &Edges,
+    kinds: &[Vec<EdgeKind>],
+    gauge: &LinkGauge,
     hook_size: f32,
     displacement: &mut Vec<Vec3>,
     tensions: &mut Vec<Vec<f32>>,
 ) {
-    for (i, node) in nodes.iter().enumerate() {
-        for (edge_index, neighbor_index) in edges.data()[i].iter().enumerate() {
-            if *neighbor_index >= nodes.len() {
-                continue; // assert that it doesn't happen?
+    let links = Links {
+        nodes,
+        edges,
+        kinds,
+        gauge,
+        hook_size,
+    };
+    for (i, tensions) in tensions.iter_mut().enumerate().take(nodes.len()) {
+        node_link_forces(i, &links, displacement, 0, tensions);
+    }
+    // sanity!(self.displacement.assert_no_nan("link forces"));
+}
+
+/// Nodes handled by one parallel task. It's fixed so that the forces are summed in the same order regardless of the thread count.
+#[cfg(feature = "parallel")]
+const CHUNK: usize = 1024;
+
+/// Same as [`link_forces`], each chunk of nodes collects its forces in its own buffer and the buffers are summed afterwards.
+/// Deterministic, but the sums are done in a different order than in [`link_forces`].
+#[cfg(feature = "parallel")]
+pub(crate) fn link_forces_parallel(
+    nodes: &[Node],
+    edges: &Edges,
+    kinds: &[Vec<EdgeKind>],
+    gauge: &LinkGauge,
+    hook_size: f32,
+    displacement: &mut [Vec3],
+    tensions: &mut [Vec<f32>],
+) {
+    use rayon::prelude::*;
+
+    let links = Links {
+        nodes,
+        edges,
+        kinds,
+        gauge,
+        hook_size,
+    };
+    let tensions = &mut tensions[..nodes.len()];
+    // each buffer covers only the nodes its chunk links to, starting at the index in the tuple
+    let buffers: Vec<(usize, Vec<Vec3>)> = tensions
+        .par_chunks_mut(CHUNK)
+        .enumerate()
+        .map(|(chunk, tensions)| {
+            let start = chunk * CHUNK;
+            let end = start + tensions.len();
+            let linked = (start..end).flat_map(|i| edges.data()[i].iter().copied());
+            let first = linked.clone().fold(start, usize::min);
+            let last = linked.fold(end - 1, usize::max).min(nodes.len() - 1);
+            let mut buffer = vec![Vec3::ZERO; last + 1 - first];
+            for (offset, tensions) in tensions.iter_mut().enumerate() {
+                node_link_forces(start + offset, &links, &mut buffer, first, tensions);
+            }
+            (first, buffer)
+        })
+        .collect();
+
+    displacement[..nodes.len()]
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(i, displacement)| {
+            for (first, buffer) in &buffers {
+                if let Some(force) = i.checked_sub(*first).and_then(|i| buffer.get(i)) {
+                    *displacement += *force;
+                }
             }
-            let neighbor = &nodes[*neighbor_index];
-            let diff = node.position - neighbor.position;
-            let tension = link_force_magnitude(diff.length(), hook_size);
-            let force: Vec3 = -diff.normalize() * tension;
-            displacement[i] += force;
-            displacement[*neighbor_index] -= force;
-            tensions[i][edge_index] = tension;
+        });
+}
+
+/// Everything the links pull with, besides the buffers they write to
+struct Links<'a> {
+    nodes: &'a [Node],
+    edges: &'a Edges,
+    kinds: &'a [Vec<EdgeKind>],
+    gauge: &'a LinkGauge,
+    hook_size: f32,
+}
+
+/// Applies forces of links from node `i`, `displacement` starts at node `first`.
+fn node_link_forces(
+    i: usize,
+    links: &Links,
+    displacement: &mut [Vec3],
+    first: usize,
+    tensions: &mut [f32],
+) {
+    let Links {
+        nodes,
+        edges,
+        kinds,
+        gauge,
+        hook_size,
+    } = *links;
+    let node = &nodes[i];
+    for ((edge_index, neighbor_index), kind) in edges.data()[i].iter().enumerate().zip(&kinds[i]) {
+        if *neighbor_index >= nodes.len() {
+            continue; // assert that it doesn't happen?
         }
+        let neighbor = &nodes[*neighbor_index];
+        let diff = node.position - neighbor.position;
+        let link = gauge.link(*kind);
+        let tension = link.stiffness * link_force_magnitude(diff.length(), link.length * hook_size);
+        let force: Vec3 = -diff.normalize() * tension;
+        displacement[i - first] += force;
+        displacement[*neighbor_index - first] -= force;
+        tensions[edge_index] = tension;
     }
-    // sanity!(self.displacement.assert_no_nan("link forces"));
 }
 
 /// Attract nodes far away, repel nodes close to each other
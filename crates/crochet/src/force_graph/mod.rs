@@ -1,8 +1,12 @@
 pub mod centroid_stuffing;
+pub mod gravity;
 pub mod link_force;
+pub mod pressure_stuffing;
+pub mod self_collision;
 pub mod simulated_plushie;
 pub mod single_loop;
 
 mod initializers;
 
 pub use initializers::Initializer;
+pub use simulated_plushie::relax::{RelaxReport, StopCondition};
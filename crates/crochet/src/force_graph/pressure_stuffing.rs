@@ -0,0 +1,180 @@
+use std::{f32::consts::PI, ops::Range};
+
+use glam::Vec3;
+
+use crate::{data::Edges, force_graph::simulated_plushie::Node};
+
+/// Triangles covering the surface of a part, wound the same way.
+///
+/// Every stitch makes a fan with the loops it's worked into (its links to earlier nodes of the part, except the previous stitch),
+/// and every two consecutive stitches make a quad with the loops below them.
+/// Magic ring closes itself the same way, fasten off tip closes the top.
+pub(crate) fn surface(edges: &Edges, part: Range<usize>) -> Vec<[usize; 3]> {
+    let anchors = |i: usize| -> Vec<usize> {
+        let mut anchors: Vec<usize> = edges.data()[i]
+            .iter()
+            .copied()
+            .filter(|&a| part.contains(&a))
+            .collect();
+        anchors.sort();
+        if anchors.len() > 1 {
+            // the previous stitch, unless it's the only link, like from the magic ring root to the first stitch
+            anchors.retain(|&a| a + 1 != i);
+        }
+        anchors
+    };
+    let linked = |a: usize, b: usize| edges.data()[a.max(b)].contains(&a.min(b));
+
+    let mut faces = vec![];
+    let mut previous_anchors: Vec<usize> = vec![];
+    for i in part.clone() {
+        let anchors = anchors(i);
+        for pair in anchors.windows(2) {
+            faces.push([i, pair[1], pair[0]]);
+        }
+        if i > part.start
+            && linked(i - 1, i)
+            && let (Some(&p), Some(&q)) = (previous_anchors.last(), anchors.first())
+        {
+            faces.push([i - 1, i, q]);
+            if p != q {
+                faces.push([i - 1, q, p]);
+            }
+        }
+        previous_anchors = anchors;
+    }
+    faces
+}
+
+/// Pushes the surface out along its normals, as if the part was filled with gas.
+/// The part aims for the volume of a sphere with the same surface area, the pressure drops as the volume gets closer to it.
+/// `firmness` is the pressure of a collapsed part. With `firmness` of 1,
+/// a stitch in the middle of collapsed fabric is pushed about as hard as a fully stretched link pulls it.
+pub fn pressure_stuffing(
+    nodes: &[Node],
+    surface: &[[usize; 3]],
+    firmness: f32,
+    hook_size: f32,
+    displacement: &mut [Vec3],
+) {
+    let faces = || {
+        surface
+            .iter()
+            .filter(|face| face.iter().all(|&i| i < nodes.len()))
+    };
+    let area = faces().count() as f32 * REST_FACE_AREA * hook_size.powi(2);
+    let target_volume = area.powf(1.5) / (6.0 * PI.sqrt());
+    let volume = enclosed_volume(nodes, surface);
+    if target_volume == 0.0 {
+        return;
+    }
+    let pressure = firmness * (1.0 - volume.abs() / target_volume).max(0.0);
+    // the part is built in either direction, normals point outside when the volume is positive
+    let multiplier = volume.signum() * pressure / hook_size.powi(2);
+
+    for face in faces() {
+        let [a, b, c] = face.map(|i| nodes[i].position);
+        // each vertex carries a third of the face, area-weighted normal is half of the cross product
+        let push = (b - a).cross(c - a) / 6.0 * multiplier;
+        for &i in face {
+            displacement[i] += push;
+        }
+    }
+}
+
+/// Area of an equilateral triangle with unit sides
+const REST_FACE_AREA: f32 = 0.4330127;
+
+/// Signed volume enclosed by the surface, faces with nodes that don't exist yet are skipped.
+pub fn enclosed_volume(nodes: &[Node], surface: &[[usize; 3]]) -> f32 {
+    let faces = || {
+        surface
+            .iter()
+            .filter(|face| face.iter().all(|&i| i < nodes.len()))
+    };
+    let count = faces().count();
+    if count == 0 {
+        return 0.0;
+    }
+    // measured around the middle of the faces, so that holes in the surface don't depend on where the part is
+    let center = faces()
+        .flat_map(|face| face.iter().map(|&i| nodes[i].position))
+        .sum::<Vec3>()
+        / (count * 3) as f32;
+    faces()
+        .map(|face| {
+            let [a, b, c] = face.map(|i| nodes[i].position - center);
+            a.dot(b.cross(c)) / 6.0
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::force_graph::{
+        Initializer,
+        link_force::LinkGauge,
+        simulated_plushie::step::{Integrator, SimulationParams},
+    };
+    use indoc::indoc;
+
+    const BALL: &str = indoc! {"
+        : MR(6)
+        : 6 inc (12)
+        : [sc, inc] x 6 (18)
+        : [2 sc, inc] x 6 (24)
+        3: 24 sc (24)
+        : [2 sc, dec] x 6 (18)
+        : [sc, dec] x 6 (12)
+        : 6 dec (6)
+        FO
+    "};
+
+    #[test]
+    fn test_surface_of_ball_is_closed() {
+        let (definition, _) = crate::parse(BALL, 1.0, &Initializer::RegularCylinder(12)).unwrap();
+        let faces = surface(&definition.edges, 0..definition.nodes.len());
+        // every edge is walked once in each direction, except around the last stitch at the tip
+        let mut walks: HashMap<(usize, usize), i32> = HashMap::new();
+        for face in &faces {
+            for k in 0..3 {
+                let (a, b) = (face[k], face[(k + 1) % 3]);
+                *walks.entry((a.min(b), a.max(b))).or_default() += if a < b { 1 } else { -1 };
+            }
+        }
+        let open_edges = walks.values().filter(|walks| **walks != 0).count();
+        assert!(open_edges <= 3, "{open_edges} open edges");
+    }
+
+    #[test]
+    fn test_pressure_keeps_ball_round() {
+        let volume_after_relaxing = |stuffing: &str| {
+            let pattern = format!("{stuffing}\n{BALL}");
+            let (definition, mut plushie) =
+                crate::parse(&pattern, 1.0, &Initializer::RegularCylinder(12)).unwrap();
+            let params = SimulationParams {
+                force_multiplier: 0.05,
+                single_loop_force: 0.0,
+                gauge: LinkGauge::UNIFORM,
+                integrator: Integrator::Gradient,
+                self_collision: None,
+                gravity: None,
+                floor: None,
+                max_timestep: 1.0,
+                max_travel: 1.0,
+            };
+            for _ in 0..1000 {
+                plushie.step(&params);
+            }
+            let faces = surface(&definition.edges, 0..definition.nodes.len());
+            enclosed_volume(plushie.nodes(), &faces)
+        };
+        let pressure = volume_after_relaxing("@stuffing = pressure");
+        let empty = volume_after_relaxing("@centroids = 0");
+        assert!(pressure.is_finite());
+        assert!(pressure > empty * 1.5, "{pressure} vs {empty}");
+    }
+}
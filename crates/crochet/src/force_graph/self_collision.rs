@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use glam::{IVec3, Vec3};
+
+use crate::{
+    data::{Edges, PartClusters},
+    force_graph::simulated_plushie::Node,
+};
+
+/// Which nodes push each other away when they get too close
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CollisionScope {
+    /// Only nodes of the same part, keeps folded fabric from passing through itself
+    Part,
+    /// Nodes of parts that are already sewn together
+    Cluster,
+    /// All nodes, also keeps parts that aren't sewn yet apart
+    Global,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelfCollision {
+    pub scope: CollisionScope,
+    /// Push between two nodes at the same spot, falls off to 0 at `hook_size` apart
+    pub force: f32,
+}
+
+/// Pushes apart nodes closer than `hook_size` that aren't linked to each other.
+/// O(N) as long as the nodes aren't crammed together,
+/// the nodes are sorted into a grid of `hook_size` cells and only nodes in neighboring cells are compared.
+pub(crate) fn self_collision_forces(
+    nodes: &[Node],
+    edges: &Edges,
+    part_clusters: &PartClusters,
+    collision: &SelfCollision,
+    hook_size: f32,
+    displacement: &mut [Vec3],
+) {
+    let grid = Grid::new(nodes, hook_size);
+    for (i, displacement) in displacement.iter_mut().enumerate().take(nodes.len()) {
+        *displacement += push_on_node(i, nodes, edges, part_clusters, collision, &grid);
+    }
+}
+
+/// Same as [`self_collision_forces`], gives exactly the same results.
+#[cfg(feature = "parallel")]
+pub(crate) fn self_collision_forces_parallel(
+    nodes: &[Node],
+    edges: &Edges,
+    part_clusters: &PartClusters,
+    collision: &SelfCollision,
+    hook_size: f32,
+    displacement: &mut [Vec3],
+) {
+    use rayon::prelude::*;
+
+    let grid = Grid::new(nodes, hook_size);
+    displacement[..nodes.len()]
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(i, displacement)| {
+            *displacement += push_on_node(i, nodes, edges, part_clusters, collision, &grid);
+        });
+}
+
+fn push_on_node(
+    i: usize,
+    nodes: &[Node],
+    edges: &Edges,
+    part_clusters: &PartClusters,
+    collision: &SelfCollision,
+    grid: &Grid,
+) -> Vec3 {
+    let node = &nodes[i];
+    let mut push = Vec3::ZERO;
+    for other_index in grid.neighbors(node.position) {
+        let other = &nodes[other_index];
+        if other_index == i
+            || !collision.scope.includes(node, other, part_clusters)
+            || linked(edges, i, other_index)
+        {
+            continue;
+        }
+        let diff = node.position - other.position;
+        let distance = diff.length();
+        if distance >= grid.cell_size || distance == 0.0 {
+            continue;
+        }
+        push += diff / distance * collision_force_magnitude(distance, grid.cell_size);
+    }
+    push * collision.force
+}
+
+impl CollisionScope {
+    fn includes(&self, a: &Node, b: &Node, part_clusters: &PartClusters) -> bool {
+        let (part_a, part_b) = (a.definition.part_index, b.definition.part_index);
+        match self {
+            CollisionScope::Part => part_a == part_b,
+            CollisionScope::Cluster => {
+                part_clusters.get_part_cluster(part_a) == part_clusters.get_part_cluster(part_b)
+            }
+            CollisionScope::Global => true,
+        }
+    }
+}
+
+fn linked(edges: &Edges, a: usize, b: usize) -> bool {
+    edges
+        .data()
+        .get(a.max(b))
+        .is_some_and(|links| links.contains(&a.min(b)))
+}
+
+/// Returns value in [0, 1], 1 for nodes at the same spot, 0 from `hook_size` apart
+pub fn collision_force_magnitude(distance: f32, hook_size: f32) -> f32 {
+    (1.0 - distance / hook_size).max(0.0).powi(2)
+}
+
+/// Node indices sorted into cubic cells
+struct Grid {
+    cell_size: f32,
+    cells: HashMap<IVec3, Vec<usize>>,
+}
+
+impl Grid {
+    fn new(nodes: &[Node], cell_size: f32) -> Self {
+        let mut cells: HashMap<IVec3, Vec<usize>> = HashMap::new();
+        for (i, node) in nodes.iter().enumerate() {
+            cells
+                .entry(cell_of(node.position, cell_size))
+                .or_default()
+                .push(i);
+        }
+        Self { cell_size, cells }
+    }
+
+    /// Nodes in the cell of `position` and the 26 cells around it, in a fixed order
+    fn neighbors(&self, position: Vec3) -> impl Iterator<Item = usize> + '_ {
+        let center = cell_of(position, self.cell_size);
+        (-1..=1)
+            .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| IVec3::new(x, y, z))))
+            .filter_map(move |offset| self.cells.get(&(center + offset)))
+            .flatten()
+            .copied()
+    }
+}
+
+fn cell_of(position: Vec3, cell_size: f32) -> IVec3 {
+    (position / cell_size).floor().as_ivec3()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::force_graph::Initializer;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const TWO_PARTS: &str = indoc! {"
+        == Body ==
+        : MR(6)
+        FO
+
+        == Head ==
+        : MR(6)
+        FO
+    "};
+
+    /// Spreads the nodes far apart, then puts nodes `a` and `b` half a hook size apart.
+    /// Returns the forces on `a` and `b`.
+    fn push_between(pattern: &str, a: usize, b: usize, scope: CollisionScope) -> (Vec3, Vec3) {
+        let (definition, plushie) =
+            crate::parse(pattern, 1.0, &Initializer::RegularCylinder(12)).unwrap();
+        let mut nodes = plushie.nodes().clone();
+        for (i, node) in nodes.iter_mut().enumerate() {
+            node.position = Vec3::new(10.0 * i as f32, 0.0, 0.0);
+        }
+        nodes[b].position = nodes[a].position + Vec3::new(0.5, 0.0, 0.0);
+        let mut part_clusters = definition.part_clusters.clone();
+        part_clusters.perform_all_joins();
+
+        let mut displacement = vec![Vec3::ZERO; nodes.len()];
+        self_collision_forces(
+            &nodes,
+            plushie.edges(),
+            &part_clusters,
+            &SelfCollision { scope, force: 1.0 },
+            1.0,
+            &mut displacement,
+        );
+        for (i, push) in displacement.iter().enumerate() {
+            if i != a && i != b {
+                assert_eq!(*push, Vec3::ZERO);
+            }
+        }
+        (displacement[a], displacement[b])
+    }
+
+    #[test]
+    fn test_pushes_close_nodes_apart() {
+        // nodes 2 and 4 are in the same magic ring, but not next to each other
+        let (a, b) = push_between(TWO_PARTS, 2, 4, CollisionScope::Part);
+        assert_eq!(a, Vec3::new(-0.25, 0.0, 0.0));
+        assert_eq!(b, -a);
+    }
+
+    #[test]
+    fn test_linked_nodes_dont_collide() {
+        let (a, b) = push_between(TWO_PARTS, 2, 3, CollisionScope::Global);
+        assert_eq!((a, b), (Vec3::ZERO, Vec3::ZERO));
+    }
+
+    #[test]
+    fn test_scopes() {
+        let a = 2;
+        let b = 10;
+        let in_part = push_between(TWO_PARTS, a, b, CollisionScope::Part);
+        assert_eq!(in_part, (Vec3::ZERO, Vec3::ZERO));
+        let in_cluster = push_between(TWO_PARTS, a, b, CollisionScope::Cluster);
+        assert_eq!(in_cluster, (Vec3::ZERO, Vec3::ZERO));
+        let global = push_between(TWO_PARTS, a, b, CollisionScope::Global);
+        assert_ne!(global.0, Vec3::ZERO);
+
+        let sewn = format!("{TWO_PARTS}\nsew(Body.R1.s1, Head.R1.s1)\n");
+        let in_sewn_cluster = push_between(&sewn, a, b, CollisionScope::Cluster);
+        assert_eq!(in_sewn_cluster, global);
+        let in_sewn_part = push_between(&sewn, a, b, CollisionScope::Part);
+        assert_eq!(in_sewn_part, (Vec3::ZERO, Vec3::ZERO));
+    }
+}
@@ -1,11 +1,19 @@
 pub mod init;
+pub mod relax;
+pub mod snapshot;
 pub mod step;
+pub mod step_stats;
 
 use glam::Vec3;
 
 use crate::{
     PlushieDef,
-    data::{DeferredEdge, Edges, Node as NodeDefinition, PartClusters},
+    acl::Stuffing,
+    data::{DeferredEdge, EdgeKind, Edges, Node as NodeDefinition, PartClusters},
+    force_graph::{
+        centroid_stuffing::{auto_centroid_count, medial_axis, resize_centroids},
+        gravity::{self, Balance},
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -28,6 +36,12 @@ pub struct SimulatedPlushie {
     displacement: Vec<Vec3>,
     /// Edge tension buffer to avoid reallocation every step. Mirrors structure of edges.
     tensions: Vec<Vec<f32>>,
+    /// Kind of every edge, mirrors structure of edges.
+    edge_kinds: Vec<Vec<EdgeKind>>,
+    /// Velocities and timestep of the integrator.
+    motion: step::Motion,
+    /// Forces of stuffing alone from the last step, kept for [`step_stats::StepStats`].
+    stuffing: Vec<Vec3>,
 }
 
 #[derive(Debug, Clone)]
@@ -39,8 +53,18 @@ pub struct Part {
     end: usize,
     /// Centroids requested for this part.
     pub centroids_wanted: usize,
+    /// Number of centroids follows the shape of the part instead of `centroids_wanted`.
+    pub auto_centroids: bool,
     /// Centroids positions.
     centroids: Vec<Vec3>,
+    /// Centroids are used only with [`Stuffing::Centroids`].
+    pub stuffing: Stuffing,
+    /// Weight added to every node of the part by gravity.
+    pub pellets: f32,
+    /// Triangles between nodes of this part, used by [`Stuffing::Pressure`].
+    surface: Vec<[usize; 3]>,
+    /// Round of every node of this part, centers of the rounds are where centroids are placed.
+    rounds: Vec<usize>,
     /// Displacement this node will be subtracted from displacement of other nodes in this part. This is done to keep that "reflecting node" in place while plushie is getting into shape.
     reflecting_node: Option<usize>,
 }
@@ -56,6 +80,12 @@ pub struct Node {
 pub struct OneByOneState {
     full_definition: PlushieDef,
     created_rings: usize,
+    /// Steps since the last advance of [`Self::grow_one_by_one`](SimulatedPlushie::grow_one_by_one)
+    steps_waited: usize,
+    /// Nodes from this index on were created by the last advance
+    fresh_nodes: usize,
+    /// Mean distance the fresh nodes moved in the last step
+    fresh_displacement: f32,
 }
 
 impl SimulatedPlushie {
@@ -90,6 +120,16 @@ impl SimulatedPlushie {
     pub fn get_tensions(&self) -> &Vec<Vec<f32>> {
         &self.tensions
     }
+
+    /// Kind of every edge, in the same structure as [`Self::edges`]
+    pub fn edge_kinds(&self) -> &Vec<Vec<EdgeKind>> {
+        &self.edge_kinds
+    }
+
+    /// Whether the plushie lying on a floor at `floor` height stands upright, see [`gravity::balance`].
+    pub fn balance(&self, floor: f32) -> Balance {
+        gravity::balance(&self.nodes, &self.parts, floor, self.hook_size)
+    }
 }
 
 impl Part {
@@ -97,6 +137,10 @@ impl Part {
         &self.name
     }
 
+    pub fn centroids(&self) -> &Vec<Vec3> {
+        &self.centroids
+    }
+
     // TODO this is just for UI, UI has to manage without it
     pub fn mock(name: String) -> Self {
         Self {
@@ -104,10 +148,52 @@ impl Part {
             start: 0,
             end: 0,
             centroids_wanted: 0,
+            auto_centroids: false,
             centroids: vec![],
+            stuffing: Stuffing::Centroids,
+            pellets: 0.0,
+            surface: vec![],
+            rounds: vec![],
             reflecting_node: None,
         }
     }
+
+    /// Adds or removes centroids to match the wanted count, removes all of them if the part doesn't use them.
+    /// Returns the range of nodes in the part that exist so far, `None` if there are none.
+    fn prepare_centroids(
+        &mut self,
+        nodes: &[Node],
+        hook_size: f32,
+    ) -> Option<std::ops::Range<usize>> {
+        if self.start >= nodes.len() {
+            return None;
+        }
+        let range = self.start..self.end.min(nodes.len());
+        let resize = match self.stuffing {
+            Stuffing::Centroids => {
+                self.auto_centroids || self.centroids.len() != self.centroids_wanted
+            }
+            Stuffing::Pressure { .. } => !self.centroids.is_empty(),
+        };
+        if resize {
+            let axis = medial_axis(&nodes[range.clone()], &self.rounds);
+            let wanted = match self.stuffing {
+                Stuffing::Centroids if self.auto_centroids => {
+                    auto_centroid_count(&axis, self.centroids.len(), hook_size)
+                }
+                Stuffing::Centroids => self.centroids_wanted,
+                Stuffing::Pressure { .. } => 0,
+            };
+            resize_centroids(
+                &mut self.centroids,
+                wanted,
+                &nodes[range.clone()],
+                &axis,
+                hook_size,
+            );
+        }
+        Some(range)
+    }
 }
 
 #[cfg(test)]
@@ -3,11 +3,16 @@ use glam::Vec3;
 use crate::{
     PlushieDef,
     acl::Action,
-    data::{Edges, PartClusters},
+    data::{EdgeKind, Edges, Node as NodeDefinition, PartClusters},
     force_graph::{
         Initializer,
-        initializers::ring,
-        simulated_plushie::{Node, OneByOneState, Part},
+        centroid_stuffing::node_rounds,
+        initializers::{ring, wound},
+        pressure_stuffing::surface,
+        simulated_plushie::{
+            Node, OneByOneState, Part,
+            step::{Motion, SimulationParams},
+        },
     },
 };
 
@@ -20,43 +25,52 @@ impl super::SimulatedPlushie {
     ) -> Self {
         assert!(definition.nodes.len() == definition.edges.len());
 
-        let node_positions = initializer.apply(definition.nodes.len() as u32, hook_size);
+        let node_positions = initializer.apply(&definition.nodes, part_limits, hook_size);
 
         let one_by_one_state = match initializer {
-            Initializer::RegularCylinder(_) => {
+            Initializer::RegularCylinder(_) | Initializer::Lathe => {
                 assert_eq!(node_positions.len(), definition.nodes.len());
                 None
             }
             Initializer::OneByOne => Some(OneByOneState {
                 full_definition: definition.clone(),
                 created_rings: 0,
+                steps_waited: 0,
+                fresh_nodes: 0,
+                // nothing to wait for before the first advance
+                fresh_displacement: 0.0,
             }),
         };
 
         let (parts, part_clusters) = extract_parts(&definition, part_limits, initializer);
+        let pattern_parts = &definition.pattern.parts;
         let nodes: Vec<Node> = definition
             .nodes
             .into_iter()
             .zip(node_positions)
             .map(|(def, pos)| Node {
+                position: wound(pos, pattern_parts[def.part_index].parameters.handedness),
                 definition: def,
-                position: pos,
                 rooted: false,
             })
             .collect();
-        let (edges, deferred_edges) = match initializer {
-            Initializer::RegularCylinder(_) => {
+        let (edges, deferred_edges, sewn) = match initializer {
+            Initializer::RegularCylinder(_) | Initializer::Lathe => {
                 let mut edges = definition.edges;
-                for edge in definition.deferred_edges {
+                for edge in &definition.deferred_edges {
                     edges.link(edge.node_a, edge.node_b);
                 }
-                (edges, vec![])
+                (edges, vec![], definition.deferred_edges)
             }
             Initializer::OneByOne => (
                 Edges::from_trimmed(definition.edges, nodes.len()),
                 definition.deferred_edges.into_iter().rev().collect(),
+                vec![],
             ),
         };
+        let definitions: Vec<NodeDefinition> =
+            nodes.iter().map(|node| node.definition.clone()).collect();
+        let edge_kinds = EdgeKind::of_all(&definitions, &edges, &sewn);
         let tensions = edges
             .data()
             .iter()
@@ -65,6 +79,8 @@ impl super::SimulatedPlushie {
 
         Self {
             displacement: vec![Vec3::ZERO; nodes.len()],
+            motion: Motion::new(nodes.len()),
+            stuffing: vec![],
             edges,
             nodes,
             parts,
@@ -73,6 +89,113 @@ impl super::SimulatedPlushie {
             one_by_one_state,
             hook_size,
             tensions,
+            edge_kinds,
+        }
+    }
+
+    /// Builds the plushie like [`Self::from`], but the first `prefix` nodes take their positions, roots and velocities from `previous`.
+    /// `prefix` is expected to come from [`PlushieDef::common_prefix`].
+    /// The remaining nodes are placed next to the nodes they link to, the same way [`Initializer::OneByOne`] places them.
+    /// With [`Initializer::OneByOne`] they are left to [`Self::advance_one_by_one`].
+    pub fn from_prefix(
+        previous: &Self,
+        prefix: usize,
+        definition: PlushieDef,
+        initializer: &Initializer,
+        hook_size: f32,
+        part_limits: &Vec<usize>,
+    ) -> Self {
+        let prefix = prefix.min(previous.nodes.len());
+        let mut plushie = Self::from(definition, &Initializer::OneByOne, hook_size, part_limits);
+        while plushie.nodes.len() < prefix {
+            match plushie.advance_one_by_one() {
+                OneByOneResult::JustFinished | OneByOneResult::Noop => break,
+                _ => (),
+            }
+        }
+        for (node, previous_node) in plushie.nodes.iter_mut().zip(&previous.nodes).take(prefix) {
+            node.position = previous_node.position;
+            node.rooted = previous_node.rooted;
+        }
+        // kept nodes carry on moving as before, instead of jumping on the first step
+        let mut velocities = previous.motion.velocities.clone();
+        velocities.resize(prefix, Vec3::ZERO);
+        velocities.resize(plushie.nodes.len(), Vec3::ZERO);
+        plushie.motion = Motion {
+            velocities,
+            ..previous.motion.clone()
+        };
+        for part in &mut plushie.parts {
+            if let Some(previous_part) = previous.parts.iter().find(|p| p.name == part.name) {
+                part.centroids = previous_part.centroids.clone();
+            }
+        }
+
+        match initializer {
+            Initializer::RegularCylinder(_) | Initializer::Lathe => loop {
+                match plushie.advance_one_by_one() {
+                    OneByOneResult::JustFinished | OneByOneResult::Noop => break,
+                    _ => (),
+                }
+            },
+            Initializer::OneByOne => (),
+        }
+        plushie
+    }
+
+    /// Continues with `definition` in place of the one the plushie was built from, taking its node origins, colors and ids.
+    /// Positions and motion are kept, so `definition` must have the same topology, see [`PlushieDef::diff`].
+    pub fn swap_definition(&mut self, definition: &PlushieDef) {
+        for (node, new_definition) in self.nodes.iter_mut().zip(&definition.nodes) {
+            node.definition = new_definition.clone();
+        }
+        if let Some(obo) = &mut self.one_by_one_state {
+            obo.full_definition = definition.clone();
+        }
+    }
+
+    /// Advances like [`Self::advance_one_by_one`] up to `params.batch` times, once the nodes created by the previous call settled.
+    /// Returns nothing while waiting for them.
+    pub fn grow_one_by_one(&mut self, params: &OneByOneParams) -> Vec<OneByOneResult> {
+        let Some(obo) = &self.one_by_one_state else {
+            return vec![OneByOneResult::Noop];
+        };
+        let settled = obo.fresh_displacement <= params.settled_below * self.hook_size;
+        if !settled && obo.steps_waited < params.max_steps {
+            return vec![];
+        }
+
+        let fresh_nodes = self.nodes.len();
+        let mut results = Vec::with_capacity(params.batch);
+        for _ in 0..params.batch.max(1) {
+            let result = self.advance_one_by_one();
+            let finished = matches!(result, OneByOneResult::JustFinished | OneByOneResult::Noop);
+            results.push(result);
+            if finished {
+                break;
+            }
+        }
+        if let Some(obo) = &mut self.one_by_one_state {
+            obo.steps_waited = 0;
+            obo.fresh_nodes = fresh_nodes;
+            obo.fresh_displacement = f32::INFINITY;
+        }
+        results
+    }
+
+    /// Grows the whole plushie without drawing it, stepping with `simulation` while waiting for the nodes to settle.
+    /// Returns the number of steps taken.
+    pub fn grow_fully(&mut self, params: &OneByOneParams, simulation: &SimulationParams) -> usize {
+        let mut steps = 0;
+        loop {
+            let finished = self.grow_one_by_one(params).iter().any(|result| {
+                matches!(result, OneByOneResult::JustFinished | OneByOneResult::Noop)
+            });
+            if finished {
+                return steps;
+            }
+            self.step(simulation);
+            steps += 1;
         }
     }
 
@@ -97,6 +220,7 @@ impl super::SimulatedPlushie {
             let (a, b) = (deferred_edge.node_a, deferred_edge.node_b);
             self.edges.link(a, b);
             self.tensions[a.max(b)].push(0.0);
+            self.edge_kinds[a.max(b)].push(EdgeKind::Sew);
             return OneByOneResult::CreatedEdge(a, b);
         }
 
@@ -121,6 +245,11 @@ impl super::SimulatedPlushie {
         self.edges.clone_next_node(&obo.full_definition.edges);
         self.tensions
             .push(vec![0.0; self.edges.last().unwrap().len()]);
+        self.edge_kinds.push(node_edge_kinds(
+            &obo.full_definition.nodes,
+            &self.edges,
+            new_index,
+        ));
         assert_eq!(self.edges.len(), new_index + 1);
         let position_basis: Vec<Vec3> = self
             .edges
@@ -152,7 +281,11 @@ impl super::SimulatedPlushie {
         obo.created_rings += 1;
 
         let mut positions = vec![part_origin];
-        let mut ring = ring(count as u32, self.hook_size, self.hook_size);
+        let part_index = obo.full_definition.nodes[start_index].part_index;
+        let handedness = obo.full_definition.pattern.parts[part_index]
+            .parameters
+            .handedness;
+        let mut ring = ring(count as u32, self.hook_size, self.hook_size, handedness);
         for node in ring.iter_mut() {
             *node = *node + part_origin;
         }
@@ -163,6 +296,11 @@ impl super::SimulatedPlushie {
             self.edges.clone_next_node(&obo.full_definition.edges);
             self.tensions
                 .push(vec![0.0; self.edges.last().unwrap().len()]);
+            self.edge_kinds.push(node_edge_kinds(
+                &obo.full_definition.nodes,
+                &self.edges,
+                start_index + i,
+            ));
             self.nodes.push(Node {
                 definition: obo.full_definition.nodes[start_index + i].clone(),
                 position: positions[i],
@@ -178,6 +316,49 @@ impl super::SimulatedPlushie {
     }
 }
 
+/// How [`SimulatedPlushie::grow_one_by_one`](super::SimulatedPlushie::grow_one_by_one) paces the growth
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OneByOneParams {
+    /// Advances per call, each creates a node, a magic ring or a link
+    pub batch: usize,
+    /// The next batch comes once the fresh nodes move less than this in a step, in hook sizes
+    pub settled_below: f32,
+    /// Steps after which the next batch comes even if the fresh nodes still move, 0 never waits
+    pub max_steps: usize,
+}
+
+impl Default for OneByOneParams {
+    /// One advance every step
+    fn default() -> Self {
+        Self {
+            batch: 1,
+            settled_below: 0.0,
+            max_steps: 1,
+        }
+    }
+}
+
+impl OneByOneState {
+    pub(super) fn record_step(&mut self, nodes: &[Node], previous_positions: &[Vec3]) {
+        self.steps_waited += 1;
+        let fresh = match self.fresh_nodes.min(nodes.len())..nodes.len() {
+            // the last advance only linked nodes
+            fresh if fresh.is_empty() => 0..nodes.len(),
+            fresh => fresh,
+        };
+        if fresh.is_empty() {
+            self.fresh_displacement = 0.0;
+            return;
+        }
+        self.fresh_displacement = nodes[fresh.clone()]
+            .iter()
+            .zip(&previous_positions[fresh.clone()])
+            .map(|(node, previous)| node.position.distance(*previous))
+            .sum::<f32>()
+            / fresh.len() as f32;
+    }
+}
+
 pub enum OneByOneResult {
     /// Created one node at given index.
     CreatedNode(usize),
@@ -192,6 +373,14 @@ pub enum OneByOneResult {
     Noop,
 }
 
+fn node_edge_kinds(nodes: &[NodeDefinition], edges: &Edges, i: usize) -> Vec<EdgeKind> {
+    edges
+        .edges_from_node(i)
+        .iter()
+        .map(|j| EdgeKind::of(nodes, i, *j))
+        .collect()
+}
+
 fn new_node_position(based_on: &Vec<Vec3>, hook_size: f32) -> Vec3 {
     if based_on.len() == 0 {
         unreachable!()
@@ -234,7 +423,12 @@ fn extract_parts(
                 start,
                 end,
                 centroids_wanted: part_def.parameters.centroids,
+                auto_centroids: part_def.parameters.auto_centroids,
                 centroids: vec![],
+                stuffing: part_def.parameters.stuffing,
+                pellets: part_def.parameters.pellets,
+                surface: surface(&definition.edges, start..end),
+                rounds: node_rounds(&definition.nodes, start..end),
                 reflecting_node: Some(start),
             }
         })
@@ -244,7 +438,7 @@ fn extract_parts(
 
     let mut clusters = definition.part_clusters.clone();
     match initializer {
-        Initializer::RegularCylinder(_) => {
+        Initializer::RegularCylinder(_) | Initializer::Lathe => {
             clusters.perform_all_joins();
             for i in 0..parts.len() {
                 let cluster = clusters.get_part_cluster(i);
@@ -0,0 +1,77 @@
+use std::time::{Duration, Instant};
+
+use super::{
+    step::SimulationParams,
+    step_stats::{StepMetrics, StepStats},
+};
+
+/// When [`SimulatedPlushie::relax`](super::SimulatedPlushie::relax) stops stepping.
+/// The plushie has converged once every given threshold is met in the same step,
+/// the limits stop it early without convergence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StopCondition {
+    /// Converged when no node moved further than this in a step
+    pub max_displacement: Option<f32>,
+    /// Converged when the mean tension of the links changed less than this in a step
+    pub mean_tension_change: Option<f32>,
+    /// Gives up after this much time
+    pub time_limit: Option<Duration>,
+    /// Gives up after this many steps
+    pub max_iterations: usize,
+}
+
+impl StopCondition {
+    fn is_met(&self, previous: &StepMetrics, current: &StepMetrics) -> bool {
+        if self.max_displacement.is_none() && self.mean_tension_change.is_none() {
+            return false;
+        }
+        self.max_displacement
+            .is_none_or(|limit| current.max_displacement < limit)
+            && self
+                .mean_tension_change
+                .is_none_or(|limit| (current.mean_tension - previous.mean_tension).abs() < limit)
+    }
+}
+
+/// Outcome of [`SimulatedPlushie::relax`](super::SimulatedPlushie::relax)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelaxReport {
+    /// Steps performed
+    pub iterations: usize,
+    /// Link and stuffing energy after the last step
+    pub final_energy: f32,
+    /// The thresholds were met, as opposed to hitting a limit
+    pub converged: bool,
+    /// Stats of the last step, all zeros when no step was performed
+    pub last_step: StepStats,
+}
+
+impl super::SimulatedPlushie {
+    /// Steps the simulation until it settles according to `stop`, without anything to display it.
+    pub fn relax(&mut self, params: &SimulationParams, stop: &StopCondition) -> RelaxReport {
+        let started = Instant::now();
+        let mut report = RelaxReport {
+            iterations: 0,
+            final_energy: 0.0,
+            converged: false,
+            last_step: StepStats::default(),
+        };
+        while report.iterations < stop.max_iterations
+            && stop
+                .time_limit
+                .is_none_or(|limit| started.elapsed() < limit)
+        {
+            let stats = self.step(params);
+            report.iterations += 1;
+            // the first step has nothing to compare the tension with
+            report.converged =
+                report.iterations > 1 && stop.is_met(&report.last_step.total, &stats.total);
+            report.final_energy = stats.total.link_energy + stats.total.stuffing_energy;
+            report.last_step = stats;
+            if report.converged {
+                break;
+            }
+        }
+        report
+    }
+}
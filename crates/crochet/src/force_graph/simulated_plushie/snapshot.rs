@@ -0,0 +1,450 @@
+use std::fmt::Display;
+
+use glam::Vec3;
+
+use crate::{
+    PlushieDef,
+    acl::Stuffing,
+    data::{EdgeKind, Edges, Node as NodeDefinition},
+    force_graph::{
+        Initializer,
+        simulated_plushie::{Node, OneByOneState, step::Motion},
+    },
+};
+
+/// Everything needed to continue a simulation where it stopped, without replaying it.
+/// The graph itself is rebuilt from the pattern, `pattern_hash` tells whether it is still the same pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub pattern_hash: u64,
+    hook_size: f32,
+    positions: Vec<Vec3>,
+    rooted: Vec<bool>,
+    edges: Vec<Vec<usize>>,
+    /// How many deferred edges were not linked yet
+    deferred_edges: usize,
+    /// How many part joins were performed
+    part_joins: usize,
+    /// Progress of [`Initializer::OneByOne`], `None` when the plushie is complete
+    growth: Option<GrowthSnapshot>,
+    parts: Vec<PartSnapshot>,
+    velocities: Vec<Vec3>,
+    timestep: f32,
+    fire_alpha: f32,
+    steps_downhill: usize,
+}
+
+/// Part settings can be changed while the simulation runs, so they are saved too
+#[derive(Debug, Clone, PartialEq)]
+struct PartSnapshot {
+    centroids_wanted: usize,
+    auto_centroids: bool,
+    centroids: Vec<Vec3>,
+    stuffing: Stuffing,
+    pellets: f32,
+    reflecting_node: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct GrowthSnapshot {
+    created_rings: usize,
+    steps_waited: usize,
+    fresh_nodes: usize,
+    fresh_displacement: f32,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SnapshotError {
+    /// Not a snapshot, or a truncated one
+    Corrupted,
+    /// Saved by a different version of the format
+    UnsupportedVersion(u32),
+    /// The pattern changed since the snapshot was taken
+    StalePattern,
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Corrupted => write!(f, "snapshot is corrupted"),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "snapshot format version {version} is not supported")
+            }
+            SnapshotError::StalePattern => {
+                write!(f, "snapshot was taken of a different pattern")
+            }
+        }
+    }
+}
+
+const MAGIC: &[u8; 8] = b"CROCHSNP";
+const VERSION: u32 = 2;
+
+/// FNV-1a, stable across platforms and releases unlike [`std::hash::DefaultHasher`]
+pub fn pattern_hash(acl_source: &str) -> u64 {
+    acl_source.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl super::SimulatedPlushie {
+    /// Saves the state of the simulation. `acl_source` must be the pattern this plushie was built from.
+    pub fn snapshot(&self, acl_source: &str) -> Snapshot {
+        Snapshot {
+            pattern_hash: pattern_hash(acl_source),
+            hook_size: self.hook_size,
+            positions: self.nodes.iter().map(|node| node.position).collect(),
+            rooted: self.nodes.iter().map(|node| node.rooted).collect(),
+            edges: self.edges.data().clone(),
+            deferred_edges: self.deferred_edges.len(),
+            part_joins: self.part_clusters.performed_joins(),
+            growth: self.one_by_one_state.as_ref().map(|obo| GrowthSnapshot {
+                created_rings: obo.created_rings,
+                steps_waited: obo.steps_waited,
+                fresh_nodes: obo.fresh_nodes,
+                fresh_displacement: obo.fresh_displacement,
+            }),
+            parts: self
+                .parts
+                .iter()
+                .map(|part| PartSnapshot {
+                    centroids_wanted: part.centroids_wanted,
+                    auto_centroids: part.auto_centroids,
+                    centroids: part.centroids.clone(),
+                    stuffing: part.stuffing,
+                    pellets: part.pellets,
+                    reflecting_node: part.reflecting_node,
+                })
+                .collect(),
+            velocities: self.motion.velocities.clone(),
+            timestep: self.motion.timestep,
+            fire_alpha: self.motion.fire_alpha,
+            steps_downhill: self.motion.steps_downhill,
+        }
+    }
+
+    /// Rebuilds the plushie saved in `snapshot`, stepping it gives the same results as stepping the original.
+    /// `definition` must be built from the same pattern, the hash is expected to be checked by the caller.
+    pub fn resume(
+        definition: PlushieDef,
+        part_limits: &Vec<usize>,
+        snapshot: &Snapshot,
+    ) -> Result<Self, SnapshotError> {
+        let full_nodes = definition.nodes.clone();
+        let mut plushie = Self::from(
+            definition,
+            &Initializer::OneByOne,
+            snapshot.hook_size,
+            part_limits,
+        );
+        let node_count = snapshot.positions.len();
+        let consistent = node_count <= full_nodes.len()
+            && snapshot.rooted.len() == node_count
+            && snapshot.edges.len() == node_count
+            && snapshot.velocities.len() <= node_count
+            && snapshot.parts.len() == plushie.parts.len()
+            && snapshot.deferred_edges <= plushie.deferred_edges.len()
+            && snapshot.part_joins <= plushie.part_clusters.joins().len()
+            && snapshot
+                .edges
+                .iter()
+                .enumerate()
+                .all(|(i, targets)| targets.iter().all(|target| *target < i))
+            && snapshot.parts.iter().all(|part| {
+                part.reflecting_node
+                    .is_none_or(|reflecting| reflecting < full_nodes.len())
+            });
+        if !consistent {
+            return Err(SnapshotError::Corrupted);
+        }
+
+        plushie.nodes = full_nodes
+            .into_iter()
+            .zip(&snapshot.positions)
+            .zip(&snapshot.rooted)
+            .map(|((definition, position), rooted)| Node {
+                definition,
+                position: *position,
+                rooted: *rooted,
+            })
+            .collect();
+        plushie.edges = Edges::from(snapshot.edges.clone());
+        plushie.tensions = snapshot
+            .edges
+            .iter()
+            .map(|targets| vec![0.0; targets.len()])
+            .collect();
+        plushie.displacement = vec![Vec3::ZERO; node_count];
+        // the next deferred edge is at the end, the linked ones were popped from there
+        let sewn = plushie.deferred_edges.split_off(snapshot.deferred_edges);
+        let definitions: Vec<NodeDefinition> = plushie
+            .nodes
+            .iter()
+            .map(|node| node.definition.clone())
+            .collect();
+        plushie.edge_kinds = EdgeKind::of_all(&definitions, &plushie.edges, &sewn);
+        for _ in 0..snapshot.part_joins {
+            plushie.part_clusters.perform_next_join();
+        }
+        plushie.one_by_one_state = match &snapshot.growth {
+            Some(growth) => plushie.one_by_one_state.map(|obo| OneByOneState {
+                created_rings: growth.created_rings,
+                steps_waited: growth.steps_waited,
+                fresh_nodes: growth.fresh_nodes,
+                fresh_displacement: growth.fresh_displacement,
+                ..obo
+            }),
+            None => None,
+        };
+        for (part, saved) in plushie.parts.iter_mut().zip(&snapshot.parts) {
+            part.centroids_wanted = saved.centroids_wanted;
+            part.auto_centroids = saved.auto_centroids;
+            part.centroids = saved.centroids.clone();
+            part.stuffing = saved.stuffing;
+            part.pellets = saved.pellets;
+            part.reflecting_node = saved.reflecting_node;
+        }
+        plushie.motion = Motion {
+            velocities: snapshot.velocities.clone(),
+            timestep: snapshot.timestep,
+            fire_alpha: snapshot.fire_alpha,
+            steps_downhill: snapshot.steps_downhill,
+        };
+        Ok(plushie)
+    }
+}
+
+impl Snapshot {
+    /// Fails with [`SnapshotError::StalePattern`] when the snapshot wasn't taken of `acl_source`
+    pub fn check_pattern(&self, acl_source: &str) -> Result<(), SnapshotError> {
+        if self.pattern_hash == pattern_hash(acl_source) {
+            Ok(())
+        } else {
+            Err(SnapshotError::StalePattern)
+        }
+    }
+
+    /// Little endian binary, floats are saved bit for bit so the simulation resumes exactly
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Writer(MAGIC.to_vec());
+        out.u32(VERSION);
+        out.u64(self.pattern_hash);
+        out.f32(self.hook_size);
+        out.vec3s(&self.positions);
+        out.usize(self.rooted.len());
+        for rooted in &self.rooted {
+            out.bool(*rooted);
+        }
+        out.usize(self.edges.len());
+        for targets in &self.edges {
+            out.usize(targets.len());
+            for target in targets {
+                out.usize(*target);
+            }
+        }
+        out.usize(self.deferred_edges);
+        out.usize(self.part_joins);
+        match &self.growth {
+            Some(growth) => {
+                out.bool(true);
+                out.usize(growth.created_rings);
+                out.usize(growth.steps_waited);
+                out.usize(growth.fresh_nodes);
+                out.f32(growth.fresh_displacement);
+            }
+            None => out.bool(false),
+        }
+        out.usize(self.parts.len());
+        for part in &self.parts {
+            out.usize(part.centroids_wanted);
+            out.bool(part.auto_centroids);
+            out.vec3s(&part.centroids);
+            match part.stuffing {
+                Stuffing::Centroids => out.0.push(0),
+                Stuffing::Pressure { firmness } => {
+                    out.0.push(1);
+                    out.f32(firmness);
+                }
+            }
+            out.f32(part.pellets);
+            out.optional(part.reflecting_node);
+        }
+        out.vec3s(&self.velocities);
+        out.f32(self.timestep);
+        out.f32(self.fire_alpha);
+        out.usize(self.steps_downhill);
+        out.0
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut input = Reader(bytes);
+        if input.take(MAGIC.len())? != MAGIC {
+            return Err(SnapshotError::Corrupted);
+        }
+        let version = input.u32()?;
+        if version != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        let pattern_hash = input.u64()?;
+        let hook_size = input.f32()?;
+        let positions = input.vec3s()?;
+        let rooted = (0..input.len()?)
+            .map(|_| input.bool())
+            .collect::<Result<_, _>>()?;
+        let edges = (0..input.len()?)
+            .map(|_| (0..input.len()?).map(|_| input.usize()).collect())
+            .collect::<Result<_, _>>()?;
+        let deferred_edges = input.usize()?;
+        let part_joins = input.usize()?;
+        let growth = if input.bool()? {
+            Some(GrowthSnapshot {
+                created_rings: input.usize()?,
+                steps_waited: input.usize()?,
+                fresh_nodes: input.usize()?,
+                fresh_displacement: input.f32()?,
+            })
+        } else {
+            None
+        };
+        let parts = (0..input.len()?)
+            .map(|_| {
+                Ok(PartSnapshot {
+                    centroids_wanted: input.usize()?,
+                    auto_centroids: input.bool()?,
+                    centroids: input.vec3s()?,
+                    stuffing: match input.take(1)?[0] {
+                        0 => Stuffing::Centroids,
+                        1 => Stuffing::Pressure {
+                            firmness: input.f32()?,
+                        },
+                        _ => return Err(SnapshotError::Corrupted),
+                    },
+                    pellets: input.f32()?,
+                    reflecting_node: input.optional()?,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        let snapshot = Self {
+            pattern_hash,
+            hook_size,
+            positions,
+            rooted,
+            edges,
+            deferred_edges,
+            part_joins,
+            growth,
+            parts,
+            velocities: input.vec3s()?,
+            timestep: input.f32()?,
+            fire_alpha: input.f32()?,
+            steps_downhill: input.usize()?,
+        };
+        if !input.0.is_empty() {
+            return Err(SnapshotError::Corrupted);
+        }
+        Ok(snapshot)
+    }
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.u64(value as u64);
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.u32(value.to_bits());
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.0.push(value as u8);
+    }
+
+    fn optional(&mut self, value: Option<usize>) {
+        match value {
+            Some(value) => {
+                self.bool(true);
+                self.usize(value);
+            }
+            None => self.bool(false),
+        }
+    }
+
+    fn vec3s(&mut self, values: &[Vec3]) {
+        self.usize(values.len());
+        for value in values {
+            self.f32(value.x);
+            self.f32(value.y);
+            self.f32(value.z);
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take(&mut self, count: usize) -> Result<&[u8], SnapshotError> {
+        if self.0.len() < count {
+            return Err(SnapshotError::Corrupted);
+        }
+        let (taken, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, SnapshotError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, SnapshotError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn usize(&mut self) -> Result<usize, SnapshotError> {
+        usize::try_from(self.u64()?).map_err(|_| SnapshotError::Corrupted)
+    }
+
+    /// Length of a list, bounded by the remaining bytes so a corrupted length can't allocate too much
+    fn len(&mut self) -> Result<usize, SnapshotError> {
+        let len = self.usize()?;
+        if len > self.0.len() {
+            return Err(SnapshotError::Corrupted);
+        }
+        Ok(len)
+    }
+
+    fn f32(&mut self) -> Result<f32, SnapshotError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    fn bool(&mut self) -> Result<bool, SnapshotError> {
+        match self.take(1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SnapshotError::Corrupted),
+        }
+    }
+
+    fn optional(&mut self) -> Result<Option<usize>, SnapshotError> {
+        Ok(if self.bool()? {
+            Some(self.usize()?)
+        } else {
+            None
+        })
+    }
+
+    fn vec3s(&mut self) -> Result<Vec<Vec3>, SnapshotError> {
+        (0..self.len()?)
+            .map(|_| Ok(Vec3::new(self.f32()?, self.f32()?, self.f32()?)))
+            .collect()
+    }
+}
@@ -1,34 +1,236 @@
 use glam::Vec3;
 
+use crate::acl::Stuffing;
 use crate::force_graph::{
-    centroid_stuffing::centroid_stuffing, link_force::link_forces, single_loop::single_loop_forces,
+    centroid_stuffing::{centroid_push, centroid_stuffing},
+    gravity::{Gravity, gravity_forces},
+    link_force::{LinkGauge, link_forces},
+    pressure_stuffing::pressure_stuffing,
+    self_collision::{SelfCollision, self_collision_forces},
+    simulated_plushie::{Node, step_stats::StepStats},
+    single_loop::single_loop_forces,
 };
+#[cfg(feature = "parallel")]
+use crate::force_graph::{
+    centroid_stuffing::{centroid_push_parallel, centroid_stuffing_parallel},
+    link_force::link_forces_parallel,
+    self_collision::self_collision_forces_parallel,
+    single_loop::single_loop_forces_parallel,
+};
+
+/// Smaller plushies are not worth the overhead of spreading the work across threads
+#[cfg(feature = "parallel")]
+pub const PARALLEL_MIN_NODES: usize = 2048;
 
 pub struct SimulationParams {
     pub force_multiplier: f32,
     pub single_loop_force: f32,
+    /// Rest length and stiffness of every kind of link
+    pub gauge: LinkGauge,
+    pub integrator: Integrator,
+    /// Repulsion between stitches that aren't linked, `None` lets them pass through each other.
+    pub self_collision: Option<SelfCollision>,
+    /// With gravity the plushie isn't held in place by its reflecting nodes, set `floor` to keep it from falling forever.
+    pub gravity: Option<Gravity>,
+    /// Height of the ground plane, nodes can't go below it.
+    pub floor: Option<f32>,
+    /// Upper bound of the timestep, in steps of [`Integrator::Gradient`]. Doesn't apply to [`Integrator::Gradient`].
+    pub max_timestep: f32,
+    /// Longest distance a node may travel in one step, in hook sizes.
+    /// Verlet and FIRE shorten the timestep to stay under it instead of blowing up.
+    pub max_travel: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Integrator {
+    /// Every node moves by its force times `force_multiplier`. Slow, but never overshoots by much.
+    Gradient,
+    /// Nodes keep velocity between steps (velocity Verlet in its leapfrog form).
+    /// `damping` is the fraction of velocity lost every step.
+    Verlet { damping: f32 },
+    /// Fast inertial relaxation engine: velocity is steered along the force, and the nodes stop whenever they start going against it.
+    Fire,
+}
+
+// FIRE constants as proposed by Bitzek et al.
+const FIRE_STEPS_BEFORE_SPEEDUP: usize = 5;
+const FIRE_TIMESTEP_INCREASE: f32 = 1.1;
+const FIRE_TIMESTEP_DECREASE: f32 = 0.5;
+const FIRE_ALPHA_START: f32 = 0.1;
+const FIRE_ALPHA_DECREASE: f32 = 0.99;
+/// Verlet shortens the timestep and slows the nodes by this factor whenever they move against the force
+const VERLET_TIMESTEP_DECREASE: f32 = 0.7;
+/// and grows it back by this factor every step.
+const VERLET_TIMESTEP_INCREASE: f32 = 1.02;
+
+/// State of the integrator carried between steps.
+#[derive(Debug, Clone)]
+pub(super) struct Motion {
+    pub(super) velocities: Vec<Vec3>,
+    pub(super) timestep: f32,
+    pub(super) fire_alpha: f32,
+    /// Steps since the nodes last moved against the force, FIRE speeds up only after a few of these.
+    pub(super) steps_downhill: usize,
+}
+
+impl Motion {
+    pub(super) fn new(nodes: usize) -> Self {
+        Self {
+            velocities: vec![Vec3::ZERO; nodes],
+            timestep: 1.0,
+            fire_alpha: FIRE_ALPHA_START,
+            steps_downhill: 0,
+        }
+    }
+
+    fn stop(&mut self) {
+        for velocity in &mut self.velocities {
+            *velocity = Vec3::ZERO;
+        }
+        self.fire_alpha = FIRE_ALPHA_START;
+        self.steps_downhill = 0;
+    }
 }
 
 impl super::SimulatedPlushie {
-    pub fn step(&mut self, params: &SimulationParams) {
+    pub fn step(&mut self, params: &SimulationParams) -> StepStats {
+        let previous_positions: Vec<Vec3> = self.nodes.iter().map(|node| node.position).collect();
+        self.integrate(params);
+        if let Some(obo) = &mut self.one_by_one_state {
+            obo.record_step(&self.nodes, &previous_positions);
+        }
+        self.step_stats(&previous_positions)
+    }
+
+    fn integrate(&mut self, params: &SimulationParams) {
         self.compute_forces(params);
+        self.compute_accelerations(params);
+        self.motion.velocities.resize(self.nodes.len(), Vec3::ZERO);
+
+        match params.integrator {
+            Integrator::Gradient => {
+                self.motion.stop();
+                self.motion.timestep = 1.0;
+                for (node, acceleration) in self.nodes.iter_mut().zip(&self.displacement) {
+                    node.position += *acceleration;
+                }
+                if let Some(floor) = params.floor {
+                    self.keep_above_floor(floor);
+                }
+                return;
+            }
+            Integrator::Verlet { damping } => {
+                if power(&self.motion.velocities, &self.displacement) < 0.0 {
+                    // moving against the force, the timestep is too long to follow the motion
+                    self.motion.timestep *= VERLET_TIMESTEP_DECREASE;
+                    for velocity in &mut self.motion.velocities {
+                        *velocity *= VERLET_TIMESTEP_DECREASE;
+                    }
+                }
+                let timestep = self.motion.timestep;
+                for (velocity, acceleration) in
+                    self.motion.velocities.iter_mut().zip(&self.displacement)
+                {
+                    *velocity = (*velocity + acceleration * timestep) * (1.0 - damping);
+                }
+            }
+            Integrator::Fire => self.fire_velocities(params),
+        }
 
-        for (node, displacement) in self.nodes.iter_mut().zip(&self.displacement) {
+        let fastest = self
+            .motion
+            .velocities
+            .iter()
+            .map(|velocity| velocity.length())
+            .fold(0.0, f32::max);
+        let travel_limit = params.max_travel * self.hook_size;
+        let timestep = if fastest * self.motion.timestep > travel_limit {
+            travel_limit / fastest
+        } else {
+            self.motion.timestep
+        };
+        // reflecting nodes have no acceleration, so they never pick up velocity
+        for (node, velocity) in self.nodes.iter_mut().zip(&mut self.motion.velocities) {
             if node.rooted {
-                continue;
+                *velocity = Vec3::ZERO;
             }
+            node.position += *velocity * timestep;
+        }
+        if let Some(floor) = params.floor {
+            self.keep_above_floor(floor);
+        }
+
+        if let Integrator::Verlet { .. } = params.integrator {
+            self.motion.timestep = (timestep * VERLET_TIMESTEP_INCREASE).min(params.max_timestep);
+        } else {
+            self.motion.timestep = timestep.min(params.max_timestep);
+        }
+    }
+
+    /// Nodes that went through the floor are put back on it and stop moving down.
+    fn keep_above_floor(&mut self, floor: f32) {
+        for (node, velocity) in self.nodes.iter_mut().zip(&mut self.motion.velocities) {
+            if node.position.y < floor {
+                node.position.y = floor;
+                velocity.y = velocity.y.max(0.0);
+            }
+        }
+    }
 
-            let reflecting_node_displacement = self.parts[node.definition.part_index]
-                .reflecting_node
-                .map_or(Vec3::ZERO, |origin_index| {
+    /// Turns forces in the displacement buffer into accelerations: scaled, relative to the reflecting node, zero for rooted nodes.
+    /// Under gravity, nodes aren't relative to the reflecting node, the plushie would never fall.
+    fn compute_accelerations(&mut self, params: &SimulationParams) {
+        let reflecting_node_displacements: Vec<Vec3> = self
+            .parts
+            .iter()
+            .map(|part| match part.reflecting_node {
+                Some(origin_index) if params.gravity.is_none() => {
                     *self.displacement.get(origin_index).unwrap_or(&Vec3::ZERO)
-                });
-            node.position +=
-                (displacement - reflecting_node_displacement) * params.force_multiplier;
+                }
+                _ => Vec3::ZERO,
+            })
+            .collect();
+        for (node, displacement) in self.nodes.iter().zip(&mut self.displacement) {
+            *displacement = if node.rooted {
+                Vec3::ZERO
+            } else {
+                (*displacement - reflecting_node_displacements[node.definition.part_index])
+                    * params.force_multiplier
+            };
         }
     }
 
-    fn compute_forces(&mut self, params: &SimulationParams) {
+    fn fire_velocities(&mut self, params: &SimulationParams) {
+        let motion = &mut self.motion;
+        let accelerations = &self.displacement;
+
+        if power(&motion.velocities, accelerations) > 0.0 {
+            let speed = norm(&motion.velocities);
+            let force = norm(accelerations);
+            if force > 0.0 {
+                let alpha = motion.fire_alpha;
+                for (velocity, acceleration) in motion.velocities.iter_mut().zip(accelerations) {
+                    *velocity = *velocity * (1.0 - alpha) + *acceleration * (alpha * speed / force);
+                }
+            }
+            motion.steps_downhill += 1;
+            if motion.steps_downhill > FIRE_STEPS_BEFORE_SPEEDUP {
+                motion.timestep =
+                    (motion.timestep * FIRE_TIMESTEP_INCREASE).min(params.max_timestep);
+                motion.fire_alpha *= FIRE_ALPHA_DECREASE;
+            }
+        } else {
+            motion.stop();
+            motion.timestep *= FIRE_TIMESTEP_DECREASE;
+        }
+
+        let timestep = motion.timestep;
+        for (velocity, acceleration) in motion.velocities.iter_mut().zip(accelerations) {
+            *velocity += *acceleration * timestep;
+        }
+    }
+
+    pub(super) fn compute_forces(&mut self, params: &SimulationParams) {
         while self.displacement.len() > self.nodes.len() {
             self.displacement.pop();
         }
@@ -42,40 +244,153 @@ impl super::SimulatedPlushie {
             ]);
         }
 
+        #[cfg(feature = "parallel")]
+        if self.nodes.len() >= PARALLEL_MIN_NODES {
+            self.compute_forces_parallel(params);
+            return;
+        }
+
         link_forces(
             &self.nodes,
             &self.edges,
+            &self.edge_kinds,
+            &params.gauge,
             self.hook_size,
             &mut self.displacement,
             &mut self.tensions,
         );
 
-        for part in &mut self.parts {
-            if part.start >= self.nodes.len() {
-                continue;
-            }
-            let end_with_obo = part.end.min(self.nodes.len());
+        self.stuffing_forces(centroid_stuffing, centroid_push);
 
-            while part.centroids.len() < part.centroids_wanted {
-                part.centroids.push(Vec3::ZERO);
-            }
-            while part.centroids.len() > part.centroids_wanted {
-                part.centroids.pop();
-            }
+        // shouldn't it take hook_size into account?
+        single_loop_forces(
+            &self.nodes,
+            params.single_loop_force,
+            &mut self.displacement,
+        );
 
-            centroid_stuffing(
-                &self.nodes[part.start..end_with_obo],
-                &mut part.centroids,
+        if let Some(collision) = &params.self_collision {
+            self_collision_forces(
+                &self.nodes,
+                &self.edges,
+                &self.part_clusters,
+                collision,
                 self.hook_size,
-                &mut self.displacement[part.start..end_with_obo],
+                &mut self.displacement,
             );
         }
 
-        // shouldn't it take hook_size into account?
-        single_loop_forces(
+        if let Some(gravity) = &params.gravity {
+            gravity_forces(&self.nodes, &self.parts, gravity, &mut self.displacement);
+        }
+    }
+
+    /// Same as [`Self::compute_forces`], spread across threads.
+    #[cfg(feature = "parallel")]
+    fn compute_forces_parallel(&mut self, params: &SimulationParams) {
+        link_forces_parallel(
+            &self.nodes,
+            &self.edges,
+            &self.edge_kinds,
+            &params.gauge,
+            self.hook_size,
+            &mut self.displacement,
+            &mut self.tensions,
+        );
+
+        self.stuffing_forces(centroid_stuffing_parallel, centroid_push_parallel);
+
+        single_loop_forces_parallel(
             &self.nodes,
             params.single_loop_force,
             &mut self.displacement,
         );
+
+        if let Some(collision) = &params.self_collision {
+            self_collision_forces_parallel(
+                &self.nodes,
+                &self.edges,
+                &self.part_clusters,
+                collision,
+                self.hook_size,
+                &mut self.displacement,
+            );
+        }
+
+        if let Some(gravity) = &params.gravity {
+            gravity_forces(&self.nodes, &self.parts, gravity, &mut self.displacement);
+        }
     }
+
+    /// Centroids push nodes of all parts in their cluster, so that parts sewn together stuff against each other.
+    /// Positions of the centroids are calculated only from nodes of their own part.
+    /// Parts stuffed with pressure push only their own nodes.
+    pub(super) fn stuffing_forces(&mut self, stuffing: CentroidStuffing, push: CentroidPush) {
+        self.stuffing.clone_from(&self.displacement);
+        let ranges: Vec<_> = self
+            .parts
+            .iter_mut()
+            .map(|part| part.prepare_centroids(&self.nodes, self.hook_size))
+            .collect();
+
+        for (part_index, range) in ranges.iter().enumerate() {
+            let Some(range) = range else {
+                continue;
+            };
+            let cluster = self.part_clusters.get_part_cluster(part_index);
+            // pushed before the centroids move, same as the nodes of their own part
+            for (other_index, other_range) in ranges.iter().enumerate() {
+                if let Some(other_range) = other_range
+                    && other_index != part_index
+                    && self.part_clusters.get_part_cluster(other_index) == cluster
+                {
+                    push(
+                        &self.nodes[other_range.clone()],
+                        &self.parts[part_index].centroids,
+                        self.hook_size,
+                        &mut self.displacement[other_range.clone()],
+                    );
+                }
+            }
+            let part = &mut self.parts[part_index];
+            match part.stuffing {
+                Stuffing::Centroids => stuffing(
+                    &self.nodes[range.clone()],
+                    &mut part.centroids,
+                    self.hook_size,
+                    &mut self.displacement[range.clone()],
+                ),
+                Stuffing::Pressure { firmness } => pressure_stuffing(
+                    &self.nodes,
+                    &part.surface,
+                    firmness,
+                    self.hook_size,
+                    &mut self.displacement,
+                ),
+            }
+        }
+        for (stuffing, displacement) in self.stuffing.iter_mut().zip(&self.displacement) {
+            *stuffing = *displacement - *stuffing;
+        }
+    }
+}
+
+type CentroidStuffing = fn(&[Node], &mut [Vec3], f32, &mut [Vec3]);
+type CentroidPush = fn(&[Node], &[Vec3], f32, &mut [Vec3]);
+
+/// Positive when the nodes move along the force, negative when against it.
+fn power(velocities: &[Vec3], accelerations: &[Vec3]) -> f32 {
+    velocities
+        .iter()
+        .zip(accelerations)
+        .map(|(velocity, acceleration)| velocity.dot(*acceleration))
+        .sum()
+}
+
+pub(super) fn norm(vectors: &[Vec3]) -> f32 {
+    vectors
+        .iter()
+        .map(|vector| vector.length_squared())
+        .sum::<f32>()
+        .sqrt()
 }
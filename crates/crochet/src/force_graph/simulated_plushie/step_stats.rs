@@ -0,0 +1,93 @@
+use std::ops::Range;
+
+use glam::Vec3;
+
+/// Summary of a single step, for comparing parameters and telling when the plushie settled.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StepStats {
+    /// The whole plushie
+    pub total: StepMetrics,
+    /// Same order as [`SimulatedPlushie::parts`](super::SimulatedPlushie::parts), parts without nodes yet have all zeros
+    pub parts: Vec<StepMetrics>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StepMetrics {
+    /// Half the sum of squared link tensions, 0 when all links are at their rest length.
+    /// Links belong to the part of the node they were made from.
+    pub link_energy: f32,
+    /// Strongest pull or push of a link
+    pub max_tension: f32,
+    /// Mean pull or push of the links, regardless of direction
+    pub mean_tension: f32,
+    /// Half the sum of squared stuffing forces, by centroids or pressure, on the nodes
+    pub stuffing_energy: f32,
+    /// Mean distance the nodes moved in this step, drops to 0 as the plushie settles
+    pub mean_displacement: f32,
+    /// Longest distance a single node moved in this step
+    pub max_displacement: f32,
+    /// Size of the box around the nodes
+    pub bounding_box: Vec3,
+}
+
+impl super::SimulatedPlushie {
+    /// Metrics of the step that moved the nodes from `previous_positions`.
+    pub(super) fn step_stats(&self, previous_positions: &[Vec3]) -> StepStats {
+        let node_count = self.nodes.len();
+        let moved: Vec<f32> = self
+            .nodes
+            .iter()
+            .zip(previous_positions)
+            .map(|(node, previous)| node.position.distance(*previous))
+            .collect();
+        StepStats {
+            total: self.metrics(0..node_count, &moved),
+            parts: self
+                .parts
+                .iter()
+                .map(|part| {
+                    self.metrics(part.start.min(node_count)..part.end.min(node_count), &moved)
+                })
+                .collect(),
+        }
+    }
+
+    fn metrics(&self, nodes: Range<usize>, moved: &[f32]) -> StepMetrics {
+        if nodes.is_empty() {
+            return StepMetrics::default();
+        }
+        let mut metrics = StepMetrics::default();
+
+        let mut links = 0;
+        let mut tension_sum = 0.0;
+        for i in nodes.clone() {
+            for (neighbor, tension) in self.edges.data()[i].iter().zip(&self.tensions[i]) {
+                if *neighbor >= self.nodes.len() {
+                    continue;
+                }
+                links += 1;
+                tension_sum += tension.abs();
+                metrics.link_energy += tension.powi(2) / 2.0;
+                metrics.max_tension = metrics.max_tension.max(tension.abs());
+            }
+        }
+        if links > 0 {
+            metrics.mean_tension = tension_sum / links as f32;
+        }
+
+        metrics.stuffing_energy = self.stuffing[nodes.clone()]
+            .iter()
+            .map(|force| force.length_squared() / 2.0)
+            .sum();
+        metrics.mean_displacement = moved[nodes.clone()].iter().sum::<f32>() / nodes.len() as f32;
+        metrics.max_displacement = moved[nodes.clone()].iter().copied().fold(0.0, f32::max);
+
+        let (min, max) = self.nodes[nodes]
+            .iter()
+            .fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(min, max), node| {
+                (min.min(node.position), max.max(node.position))
+            });
+        metrics.bounding_box = max - min;
+        metrics
+    }
+}
@@ -3,20 +3,36 @@ use glam::Vec3;
 use crate::{data::Peculiarity, force_graph::simulated_plushie::Node};
 
 pub fn single_loop_forces(nodes: &[Node], multiplier: f32, displacement: &mut [Vec3]) {
-    for (i, node) in nodes.iter().enumerate() {
-        let (push_plane_spec, direction) = match node.definition.peculiarity {
-            Some(Peculiarity::BLO(x)) => (x, 1.0),
-            Some(Peculiarity::FLO(x)) => (x, -1.0),
-            _ => continue,
-        };
-        let a: Vec3 = nodes[push_plane_spec.0].position;
-        let b: Vec3 = nodes[push_plane_spec.1].position;
-        let c: Vec3 = nodes[push_plane_spec.2].position;
-        let normal = based_on_push_plane(a, b, c, direction);
-        displacement[i] += normal * multiplier;
+    for (node, displacement) in nodes.iter().zip(displacement) {
+        *displacement += single_loop_force(nodes, node) * multiplier;
     }
 }
 
+/// Same as [`single_loop_forces`], gives exactly the same results.
+#[cfg(feature = "parallel")]
+pub fn single_loop_forces_parallel(nodes: &[Node], multiplier: f32, displacement: &mut [Vec3]) {
+    use rayon::prelude::*;
+
+    nodes
+        .par_iter()
+        .zip(displacement)
+        .for_each(|(node, displacement)| {
+            *displacement += single_loop_force(nodes, node) * multiplier;
+        });
+}
+
+fn single_loop_force(nodes: &[Node], node: &Node) -> Vec3 {
+    let (push_plane_spec, direction) = match node.definition.outside_peculiarity() {
+        Some(Peculiarity::BLO(x)) => (x, 1.0),
+        Some(Peculiarity::FLO(x)) => (x, -1.0),
+        _ => return Vec3::ZERO,
+    };
+    let a: Vec3 = nodes[push_plane_spec.0].position;
+    let b: Vec3 = nodes[push_plane_spec.1].position;
+    let c: Vec3 = nodes[push_plane_spec.2].position;
+    based_on_push_plane(a, b, c, direction)
+}
+
 fn based_on_push_plane(a: Vec3, b: Vec3, c: Vec3, direction: f32) -> Vec3 {
     let ab = b - a;
     let ac = c - a;
@@ -0,0 +1,142 @@
+//! Feeds random ACL to [`crate::parse`]. Any input is allowed to produce an error, none may panic.
+//! Inputs are built from grammar fragments, so most of them get past the lexer and reach the hook.
+
+use crate::force_graph::Initializer;
+
+/// xorshift64, good enough to pick fragments and stays reproducible without extra dependencies
+struct Random(u64);
+
+impl Random {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, options: &[&'a str]) -> &'a str {
+        options[self.below(options.len())]
+    }
+
+    fn number(&mut self) -> String {
+        match self.below(10) {
+            0 => "0".into(),
+            1 => "99".into(),
+            _ => (1 + self.below(12)).to_string(),
+        }
+    }
+}
+
+const LABELS: &[&str] = &["a", "b", "c", "R1.s1", "R2.s3", "Body.R1.s1", "Arm.R2.s1"];
+
+fn action(rng: &mut Random) -> String {
+    match rng.below(14) {
+        0 => format!("MR({})", rng.number()),
+        1 => format!("mark({})", rng.pick(LABELS)),
+        2 => format!("goto({})", rng.pick(LABELS)),
+        3 => format!("sew({}, {})", rng.pick(LABELS), rng.pick(LABELS)),
+        4 => format!("color({}, 0, 255)", rng.number()),
+        5 => rng.pick(&["FO", "FLO", "BLO", "BL", "slst"]).into(),
+        6 => format!("[{}] x {}", stitches(rng), rng.number()),
+        7 => format!("{} {}", rng.number(), rng.pick(&["sc", "inc", "dec"])),
+        _ => rng.pick(&["sc", "inc", "dec"]).into(),
+    }
+}
+
+fn stitches(rng: &mut Random) -> String {
+    let count = 1 + rng.below(4);
+    (0..count)
+        .map(|_| action(rng))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn line(rng: &mut Random) -> String {
+    match rng.below(12) {
+        0 => format!("== {} ==", rng.pick(&["Body", "Arm", "Head"])),
+        1 => format!("@centroids = {}", rng.number()),
+        2 => action(rng),
+        3 => format!("# {}", stitches(rng)),
+        _ => {
+            let label = match rng.below(4) {
+                0 => String::new(),
+                1 => rng.number(),
+                2 => format!("R{}", rng.number()),
+                _ => format!("R{}-R{}", rng.number(), rng.number()),
+            };
+            let round_end = match rng.below(3) {
+                0 => format!(" ({})", rng.number()),
+                _ => String::new(),
+            };
+            format!("{label}: {}{round_end}", stitches(rng))
+        }
+    }
+}
+
+fn program(rng: &mut Random) -> String {
+    let mut lines = vec![];
+    if rng.below(3) > 0 {
+        lines.push(format!("R1: MR({})", rng.number()));
+    }
+    for _ in 0..rng.below(10) {
+        lines.push(line(rng));
+    }
+    lines.join("\n")
+}
+
+fn parse_all_initializers(acl: &str) {
+    for initializer in [
+        Initializer::RegularCylinder(12),
+        Initializer::OneByOne,
+        Initializer::Lathe,
+    ] {
+        let result = crate::parse(acl, 1.0, &initializer);
+        if let Err(crate::errors::Error::Hook(error)) = &result {
+            assert!(
+                !error.code.means_bug_in_crate(),
+                "{error} should have been caught before building the graph:\n{acl}"
+            );
+        }
+        if let Ok((definition, plushie)) = result {
+            let (_, mut plushie, _) =
+                crate::reparse(acl, 1.0, &initializer, &definition, &plushie).unwrap();
+            while !matches!(
+                plushie.advance_one_by_one(),
+                crate::force_graph::simulated_plushie::init::OneByOneResult::JustFinished
+                    | crate::force_graph::simulated_plushie::init::OneByOneResult::Noop
+            ) {}
+        }
+    }
+}
+
+#[test]
+fn test_random_programs_do_not_panic() {
+    let mut rng = Random(0x5eed_c0c7);
+    for _ in 0..5000 {
+        let acl = program(&mut rng);
+        let result = std::panic::catch_unwind(|| parse_all_initializers(&acl));
+        assert!(result.is_ok(), "parsing panicked on:\n{acl}");
+    }
+}
+
+#[test]
+fn test_truncated_programs_do_not_panic() {
+    let acl = indoc::indoc! {"
+        == Body ==
+        @centroids = 2
+        R1: MR(6)
+        R2: FLO, 6 inc (12)
+        R3-R4: [sc, inc] x 6, mark(a) (18)
+        FO
+        == Arm ==
+        : MR(4), mark(b)
+        : BLO, 4 sc
+        sew(a, b), goto(Body.R3.s2)
+        color(255, 0, 0)
+    "};
+    for end in 0..=acl.len() {
+        let truncated = &acl[..end];
+        let result = std::panic::catch_unwind(|| parse_all_initializers(truncated));
+        assert!(result.is_ok(), "parsing panicked on:\n{truncated}");
+    }
+}
@@ -1,15 +1,17 @@
 use std::fmt::Display;
 
 pub use crate::acl::Label;
-use crate::acl::Origin;
+use crate::acl::{Origin, RepetitionPath};
 
 #[derive(Debug)]
 pub struct Error {
     pub code: ErrorCode,
     pub origin: Option<Origin>,
+    /// Which copy of the origin failed, when it was expanded by repetitions
+    pub path: RepetitionPath,
 }
 
-// TODO many of those should be unreachable given correct pattern parser (BadStarter, AnonymousMrInTheMiddle, DuplicateLabel, UnknownLabel)
+// TODO AnonymousMrInTheMiddle should be unreachable given correct pattern parser
 #[derive(Debug, PartialEq)]
 pub enum ErrorCode {
     Internal(String),
@@ -37,10 +39,12 @@ pub enum ErrorCode {
     },
     // TODO this variant was used for "arounds". It should also be used with regular repetitions. With any repetition, marks and gotos make no sense.
     IllegalActionInRepetition,
+    /// Both marks of a sew point at the same node
+    SewToSameNode,
 }
 
 impl ErrorCode {
-    /// Some situations should be prevented by ACL parser
+    /// Some situations should be prevented by ACL parser or [`PatternAst::analyze`](crate::acl::PatternAst::analyze)
     pub fn means_bug_in_crate(&self) -> bool {
         use ErrorCode::*;
         match self {
@@ -48,19 +52,20 @@ impl ErrorCode {
             BadStarter => true,
             DuplicateLabel(_) => true,
             UnknownLabel(_) => true,
+            UselessMark => true,
+            FORequires2Anchors => true,
+            SingleLoopNoGrandparent => true,
+            IllegalActionInRepetition => true,
             // ---
             Empty => false,
             AnonymousMrInTheMiddle => false,
-            UselessMark => false,
             NoAnchorToPullThrough => false,
-            FORequires2Anchors => false,
             SingleLoopOnNonAnchored => false,
-            SingleLoopNoGrandparent => false,
             ChainOfZero => false,
             ChainAfterChain => false,
             TooManyAnchorsForFO => false,
             WrongAnnotation { .. } => false,
-            IllegalActionInRepetition => false,
+            SewToSameNode => false,
         }
     }
 }
@@ -75,10 +80,14 @@ impl Display for Error {
             ),
             None => format!("without specified origin"),
         };
+        let path_msg = match self.path.to_string().as_str() {
+            "" => String::new(),
+            path => format!(" ({path})"),
+        };
 
         write!(
             f,
-            "{:?} {origin_msg}", // to display the actual text, or line pos, formatter need to analyze the input string
+            "{:?} {origin_msg}{path_msg}", // to display the actual text, or line pos, formatter need to analyze the input string
             self.code,
         )
     }
@@ -39,6 +39,8 @@ impl Hook {
             peculiarity: None,
             parent: None,
             part_index: self.now.part,
+            inside_out: self.params.inside_out.get(self.now.part) == Some(&true),
+            id: Default::default(),
         });
         self.edges.grow(); // prepare place for the next node
         NodeBuilder {
@@ -22,13 +22,18 @@ impl Hook {
         // ring B can be accessed by goto(X)
 
         let starting_anchor = self.now.cursor;
-        // TODO won't this panic?
-        let attachment_anchor = self.labels.get(label).unwrap().cursor - 1;
+        let attachment_anchor = self
+            .labels
+            .get(label)
+            .ok_or_else(|| ErrorCode::UnknownLabel(label.clone()))?
+            .cursor
+            .checked_sub(1)
+            .ok_or(ErrorCode::UselessMark)?;
         let new_anchors: Vec<usize>;
         (new_anchors, self) =
             StitchBuilder::linger(self, origin)?.attaching_chain(*chain_size, attachment_anchor)?;
         let mut moment_b;
-        (self, moment_b) = self.split_moment(attachment_anchor, new_anchors);
+        (self, moment_b) = self.split_moment(attachment_anchor, new_anchors)?;
         // let ring_b = self.split_current_moment(attaching_anchor, new_anchors);
 
         if let Some(Action::Mark(ring_b_label)) = &self.last_mark {
@@ -64,7 +69,11 @@ impl Hook {
             .get(label)
             .ok_or_else(|| ErrorCode::UnknownLabel(label.clone()))?
             .clone();
-        assert!(self.now.part == target.part);
+        if self.now.part != target.part {
+            return Err(ErrorCode::Internal(
+                "merged anchors should belong to the same part".into(),
+            ));
+        }
 
         self.override_previous_node = Some(self.previous_stitch());
         target.cursor = self.now.cursor;
@@ -83,10 +92,14 @@ impl Hook {
         }
     }
 
-    fn split_moment(mut self, attachment_anchor: usize, new_anchors: Vec<usize>) -> (Self, Moment) {
-        let (moment_a, moment_b) = split_moment(&mut self.now, attachment_anchor, new_anchors);
+    fn split_moment(
+        mut self,
+        attachment_anchor: usize,
+        new_anchors: Vec<usize>,
+    ) -> Result<(Self, Moment), ErrorCode> {
+        let (moment_a, moment_b) = split_moment(&mut self.now, attachment_anchor, new_anchors)?;
         self.now = moment_a;
-        (self, moment_b)
+        Ok((self, moment_b))
     }
 }
 
@@ -94,12 +107,12 @@ fn split_moment(
     source: &mut Moment,
     attachment_anchor: usize,
     new_anchors: Vec<usize>,
-) -> (Moment, Moment) {
+) -> Result<(Moment, Moment), ErrorCode> {
     let attachment_i = source
         .anchors
         .iter()
         .position(|x| *x == attachment_anchor)
-        .expect("attachment anchor present in current ring"); // TODO real error handling
+        .ok_or_else(|| ErrorCode::Internal("attachment anchor not in current ring".into()))?;
     let mut ring_a = source.anchors.split_off(attachment_i);
     source.anchors.extend(new_anchors.iter().rev());
     let ring_b = &source.anchors;
@@ -122,7 +135,7 @@ fn split_moment(
         part: source.part,
     };
 
-    (moment_a, moment_b)
+    Ok((moment_a, moment_b))
 }
 
 #[cfg(test)]
@@ -137,7 +150,7 @@ mod tests {
             working_on: WorkingLoops::Both,
             part: 0,
         };
-        let (moment_a, moment_b) = split_moment(&mut source, 6, [13, 14, 15, 16].into());
+        let (moment_a, moment_b) = split_moment(&mut source, 6, [13, 14, 15, 16].into()).unwrap();
         println!("{:?} {:?}", moment_a.anchors, moment_b.anchors);
         assert_eq!(moment_a.anchors.len(), 9);
         assert_eq!(moment_b.anchors.len(), 9);
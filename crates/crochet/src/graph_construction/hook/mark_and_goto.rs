@@ -1,5 +1,8 @@
-use super::Hook;
-use crate::{acl::Label, graph_construction::ErrorCode};
+use super::{Hook, Moment, Queue};
+use crate::{
+    acl::{Action, Label},
+    graph_construction::ErrorCode,
+};
 use ErrorCode::*;
 
 impl Hook {
@@ -27,4 +30,61 @@ impl Hook {
         }
         Ok(())
     }
+
+    /// Like [`Hook::save`] right after the `stitch`-th stitch of the magic ring, as if the ring was worked one stitch at a time.
+    pub(super) fn save_ring_stitch(
+        &mut self,
+        label: &Label,
+        stitch: usize,
+    ) -> Result<(), ErrorCode> {
+        let Some(Action::MR(size)) = self.last_stitch else {
+            return Err(Internal(
+                "ring stitch mark should follow a magic ring".into(),
+            ));
+        };
+        if stitch == 0 || stitch > size {
+            return Err(Internal(format!("magic ring has no stitch {stitch}")));
+        }
+        let ring_root = self.now.cursor - 1 - size;
+        let node = ring_root + stitch;
+        self.mark_to_node.insert(label.clone(), node);
+
+        let moment = Moment {
+            cursor: node + 1,
+            anchors: Queue::from_iter(ring_root + 1..=node),
+            ..self.now.clone()
+        };
+        if self.labels.insert(label.clone(), moment).is_some() {
+            return Err(DuplicateLabel(label.clone()));
+        }
+        Ok(())
+    }
+
+    /// Like [`Hook::save`] between the two stitches of the increase, as if it was worked as two separate stitches.
+    pub(super) fn save_first_of_inc(&mut self, label: &Label) -> Result<(), ErrorCode> {
+        let Some(Action::Inc) = self.last_stitch else {
+            return Err(Internal(
+                "first of inc mark should follow an increase".into(),
+            ));
+        };
+        let node = self.now.cursor - 2;
+        let anchor = self.nodes[node]
+            .parent
+            .ok_or_else(|| Internal("increase should be anchored".into()))?;
+        self.mark_to_node.insert(label.clone(), node);
+
+        // the second half was not pulled over yet, and the anchor was not left behind
+        let mut anchors = self.now.anchors.clone();
+        anchors.pop_back();
+        anchors.push_front(anchor);
+        let moment = Moment {
+            cursor: node + 1,
+            anchors,
+            ..self.now.clone()
+        };
+        if self.labels.insert(label.clone(), moment).is_some() {
+            return Err(DuplicateLabel(label.clone()));
+        }
+        Ok(())
+    }
 }
@@ -3,6 +3,7 @@ mod attaching;
 mod mark_and_goto;
 mod part_joiner;
 mod perform;
+mod snapshot;
 mod starters;
 mod stitch_builder;
 
@@ -10,15 +11,19 @@ use crate::{
     ColorRgb,
     acl::{
         Action::{self},
-        Label,
+        Handedness, Label,
     },
     data::{DeferredEdge, Edges, InitialGraph, Node, PartClusters},
     graph_construction::{errors::ErrorCode, hook::part_joiner::PartJoiner},
 };
+pub use snapshot::HookSnapshot;
 use std::collections::HashMap;
 pub use std::collections::VecDeque as Queue;
 use stitch_builder::StitchBuilder;
 
+/// FO with more anchors would create a node with too many links, the hook rejects it
+pub(crate) const ANCHORS_FOR_FO_LIMIT: usize = 12;
+
 #[derive(Debug, PartialEq, Clone, Default)]
 pub struct HookParams {
     // in the poc, FO in a round of too many nodes could break the simulation
@@ -26,6 +31,10 @@ pub struct HookParams {
     // should this parameter be exposed to CAD?
     pub tip_from_fo: bool,
     pub enforce_counts: bool,
+    /// Handedness of each part, by part index. Parts not listed are right-handed.
+    pub handedness: Vec<Handedness>,
+    /// Parts turned inside out, by part index. Parts not listed are right side out.
+    pub inside_out: Vec<bool>,
 }
 
 #[derive(Clone, Debug)]
@@ -37,13 +46,13 @@ pub enum WorkingLoops {
 
 /// Context of hook working at given cursor
 #[derive(Clone, Debug)]
-struct Moment {
+pub struct Moment {
     /// Node index to be created
-    cursor: usize,
-    anchors: Queue<usize>,
-    working_on: WorkingLoops,
+    pub cursor: usize,
+    pub anchors: Queue<usize>,
+    pub working_on: WorkingLoops,
     /// Index of the part it is working on
-    part: usize,
+    pub part: usize,
 }
 
 impl Default for Moment {
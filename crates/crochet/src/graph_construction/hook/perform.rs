@@ -77,8 +77,10 @@ impl Hook {
             BL => self.now.working_on = WorkingLoops::Both,
             Goto(label) => self.restore(label)?,
             Mark(label) => self.save(label)?,
+            MarkRingStitch(label, stitch) => self.save_ring_stitch(label, *stitch)?,
+            MarkFirstOfInc(label) => self.save_first_of_inc(label)?,
             MR(count) => {
-                self.magic_ring(*count, action_with_origin);
+                self.magic_ring(*count, action_with_origin)?;
             }
             BeginPart => {}
             EndPart => {
@@ -113,6 +115,9 @@ impl Hook {
                 let Some(right) = self.mark_to_node.get(right) else {
                     return Err(UnknownLabel(right.clone()));
                 };
+                if left == right {
+                    return Err(SewToSameNode);
+                }
                 let lpart = part_of_node(&self.part_limits, left);
                 let rpart = part_of_node(&self.part_limits, right);
                 let happens_with_node = self.nodes.len();
@@ -137,6 +142,8 @@ impl Hook {
             | FO
             | Action::Color(_)
             | Sew(..)
+            | MarkRingStitch(..)
+            | MarkFirstOfInc(..)
             | EnforceAnchors(..)
             | BeginPart
             | EndPart => self.last_mark = None,
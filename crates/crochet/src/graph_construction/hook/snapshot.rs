@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use super::{Hook, Moment};
+use crate::{
+    ColorRgb,
+    acl::{ActionWithOrigin, Label},
+    data::{DeferredEdge, Node, NodeIndex},
+};
+
+/// State of the hook right after performing an action, see [`HookStepper`](crate::HookStepper).
+#[derive(Clone, Debug)]
+pub struct HookSnapshot {
+    /// Number of actions performed before this one
+    pub step: usize,
+    pub action: ActionWithOrigin,
+    pub now: Moment,
+    /// Moments saved by marks
+    pub labels: HashMap<Label, Moment>,
+    pub color: ColorRgb,
+    /// Node the next stitch will be linked to instead of the one right before the cursor (set by goto)
+    pub override_previous_node: Option<NodeIndex>,
+    /// Index of the first node in `created_nodes`
+    pub first_created_node: NodeIndex,
+    pub created_nodes: Vec<Node>,
+    /// Links made by the action, the higher index goes first
+    pub created_edges: Vec<(NodeIndex, NodeIndex)>,
+    pub created_deferred_edges: Vec<DeferredEdge>,
+}
+
+/// Sizes of the hook's buffers, used to tell what the next action added
+#[derive(Clone, Debug, Default)]
+pub(crate) struct HookProgress {
+    nodes: usize,
+    links: Vec<usize>,
+    deferred_edges: usize,
+}
+
+impl Hook {
+    pub(crate) fn progress(&self) -> HookProgress {
+        HookProgress {
+            nodes: self.nodes.len(),
+            links: self.edges.iter().map(|links| links.len()).collect(),
+            deferred_edges: self.deferred_edges.len(),
+        }
+    }
+
+    pub(crate) fn snapshot(
+        &self,
+        step: usize,
+        action: ActionWithOrigin,
+        before: &HookProgress,
+    ) -> HookSnapshot {
+        let mut created_edges = vec![];
+        for (node, links) in self.edges.iter().enumerate() {
+            let known = before.links.get(node).copied().unwrap_or(0);
+            for &target in links.iter().skip(known) {
+                created_edges.push((node, target));
+            }
+        }
+        HookSnapshot {
+            step,
+            action,
+            now: self.now.clone(),
+            labels: self.labels.clone(),
+            color: self.color,
+            override_previous_node: self.override_previous_node,
+            first_created_node: before.nodes,
+            created_nodes: self.nodes[before.nodes..].to_vec(),
+            created_edges,
+            created_deferred_edges: self.deferred_edges[before.deferred_edges..].to_vec(),
+        }
+    }
+}
@@ -5,7 +5,10 @@ use crate::{
     ColorRgb,
     acl::ActionWithOrigin,
     data::Peculiarity,
-    graph_construction::hook::{Edges, HookParams, Moment, WorkingLoops},
+    graph_construction::{
+        ErrorCode,
+        hook::{Edges, HookParams, Moment, WorkingLoops},
+    },
 };
 
 const DEFAULT_COLOR: ColorRgb = [255, 0, 255];
@@ -30,8 +33,16 @@ impl Hook {
         }
     }
 
-    pub(super) fn magic_ring(&mut self, size: usize, origin: &ActionWithOrigin) {
-        assert_eq!(self.edges.last().unwrap().len(), 0);
+    pub(super) fn magic_ring(
+        &mut self,
+        size: usize,
+        origin: &ActionWithOrigin,
+    ) -> Result<(), ErrorCode> {
+        if self.edges.last().is_none_or(|links| !links.is_empty()) {
+            return Err(ErrorCode::Internal(
+                "magic ring should start without pending links".into(),
+            ));
+        }
 
         let ring_root = self.now.cursor;
         let ring_end = ring_root + size;
@@ -55,7 +66,6 @@ impl Hook {
         self.now.anchors = Queue::from_iter(ring_root + 1..=ring_end);
         self.now.cursor = ring_end + 1;
         self.now.working_on = WorkingLoops::Both;
-
-        assert_eq!(self.edges.last().unwrap().len(), 0);
+        Ok(())
     }
 }
@@ -1,6 +1,6 @@
-use super::Hook;
+use super::{ANCHORS_FOR_FO_LIMIT, Hook};
 use crate::{
-    acl::ActionWithOrigin,
+    acl::{ActionWithOrigin, Handedness},
     data::{Peculiarity, PointsOnPushPlane},
     graph_construction::{ErrorCode, hook::WorkingLoops},
 };
@@ -137,7 +137,6 @@ impl<'a> StitchBuilder<'a> {
 
         let tip = hook.now.cursor;
         let anchors_num = hook.now.anchors.len();
-        const ANCHORS_FOR_FO_LIMIT: usize = 12;
         if anchors_num > ANCHORS_FOR_FO_LIMIT {
             log::debug!(
                 "Too many anchors for FO (limit: {ANCHORS_FOR_FO_LIMIT}, got: {anchors_num})"
@@ -160,7 +159,11 @@ impl<'a> StitchBuilder<'a> {
         let grandparent = self.hook.nodes[mother]
             .parent
             .ok_or(SingleLoopNoGrandparent)?;
-        Ok((father, mother, grandparent))
+        // mirrored work flips the normal of the plane, swapping the first two points flips it back
+        match self.hook.params.handedness.get(self.hook.now.part) {
+            Some(Handedness::Left) => Ok((mother, father, grandparent)),
+            Some(Handedness::Right) | None => Ok((father, mother, grandparent)),
+        }
     }
 }
 
@@ -0,0 +1,158 @@
+use super::{
+    errors::{Error as HookError, ErrorCode},
+    hook::{Hook, HookParams, HookSnapshot},
+};
+use crate::{
+    acl::{ActionWithOrigin, Flow},
+    data::InitialGraph,
+};
+
+/// Runs the hook one action at a time, exposing its state after each of them.
+/// Meant for debugging how a pattern turns into a graph.
+pub struct HookStepper<F: Flow> {
+    flow: F,
+    /// None once an action failed
+    hook: Option<Hook>,
+    step: usize,
+}
+
+impl<F: Flow> HookStepper<F> {
+    pub fn new(flow: F, params: HookParams) -> Result<Self, HookError> {
+        if flow.peek().is_none() {
+            return Err(HookError {
+                code: ErrorCode::Empty,
+                origin: None,
+                path: Default::default(),
+            });
+        }
+        Ok(Self {
+            flow,
+            hook: Some(Hook::new(params)),
+            step: 0,
+        })
+    }
+
+    /// Performs the next action and describes what it did.
+    /// Returns None when the flow is exhausted or a previous action failed.
+    pub fn step(&mut self) -> Option<Result<HookSnapshot, HookError>> {
+        let before = self.hook.as_ref()?.progress();
+        let step = self.step;
+        Some(
+            self.perform_next()?
+                .map(|(hook, action)| hook.snapshot(step, action, &before)),
+        )
+    }
+
+    /// Performs all the remaining actions.
+    pub(crate) fn finish(mut self) -> Result<InitialGraph, HookError> {
+        while let Some(result) = self.perform_next() {
+            result?;
+        }
+        match self.hook {
+            Some(hook) => Ok(hook.finish()),
+            None => Err(HookError {
+                code: ErrorCode::Internal("hook already failed at an earlier step".into()),
+                origin: None,
+                path: Default::default(),
+            }),
+        }
+    }
+
+    fn perform_next(&mut self) -> Option<Result<(&Hook, ActionWithOrigin), HookError>> {
+        let hook = self.hook.take()?;
+        let Some(action_with_origin) = self.flow.next_with_origin() else {
+            self.hook = Some(hook);
+            return None;
+        };
+        let action = &action_with_origin.action;
+        let origin = &action_with_origin.origin;
+        log::trace!("Performing [{}] {action:?}. Origin: {origin:?}", self.step);
+        self.step += 1;
+        match hook.perform(&action_with_origin) {
+            Ok(hook) => Some(Ok((self.hook.insert(hook), action_with_origin))),
+            Err(code) => Some(Err(HookError {
+                code,
+                origin: *origin,
+                path: action_with_origin.path.clone(),
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acl::{Action, PatternBuilder};
+    use pretty_assertions::assert_eq;
+
+    fn snapshots(acl: &str) -> Vec<HookSnapshot> {
+        let pattern = PatternBuilder::parse(acl).unwrap();
+        let mut stepper = crate::step_hook(&pattern).unwrap();
+        std::iter::from_fn(|| stepper.step())
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_snapshots_describe_each_action() {
+        let steps = snapshots(indoc::indoc! {"
+            : MR(3)
+            : sc, mark(a), 2 sc
+            goto(a)
+        "});
+        let actions: Vec<Action> = steps.iter().map(|s| s.action.action.clone()).collect();
+        assert_eq!(
+            actions,
+            vec![
+                Action::BeginPart,
+                Action::MR(3),
+                Action::Sc,
+                Action::Mark("a".into()),
+                Action::Sc,
+                Action::Sc,
+                Action::Goto("a".into()),
+                Action::EndPart,
+            ]
+        );
+
+        let mr = &steps[1];
+        assert_eq!(mr.first_created_node, 0);
+        assert_eq!(mr.created_nodes.len(), 4);
+        assert_eq!(
+            mr.created_edges,
+            vec![(1, 0), (2, 0), (2, 1), (3, 0), (3, 2)]
+        );
+        assert_eq!(mr.now.cursor, 4);
+        assert_eq!(mr.now.anchors, [1, 2, 3]);
+
+        let sc = &steps[2];
+        assert_eq!(sc.first_created_node, 4);
+        assert_eq!(sc.created_nodes.len(), 1);
+        assert_eq!(sc.created_edges, vec![(4, 1), (4, 3)]);
+        assert_eq!(sc.now.anchors, [2, 3, 4]);
+
+        let mark = &steps[3];
+        assert!(mark.created_nodes.is_empty());
+        assert_eq!(mark.labels["a"].cursor, 5);
+
+        let goto = &steps[6];
+        assert_eq!(goto.now.cursor, 7);
+        assert_eq!(goto.now.anchors, mark.now.anchors);
+        assert_eq!(goto.override_previous_node, Some(4));
+
+        for (index, step) in steps.iter().enumerate() {
+            assert_eq!(step.step, index);
+        }
+    }
+
+    #[test]
+    fn test_stepping_stops_after_error() {
+        let pattern = PatternBuilder::parse(": MR(3)\n: 3 sc\nFO\n: sc").unwrap();
+        let mut stepper = crate::step_hook(&pattern).unwrap();
+        let error = std::iter::from_fn(|| stepper.step())
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!(error.code, ErrorCode::NoAnchorToPullThrough);
+        assert!(stepper.step().is_none());
+    }
+}
@@ -5,41 +5,122 @@ pub mod acl;
 pub mod errors;
 mod graph_construction;
 mod plushie_definition;
+pub mod stats;
+pub mod validation;
 
+pub use graph_construction::{HookError, HookSnapshot, HookStepper, Moment, WorkingLoops};
 pub use plushie_definition::*;
 
 use crate::{
-    acl::PatternBuilder, errors::Error, force_graph::Initializer,
-    force_graph::simulated_plushie::SimulatedPlushie,
+    acl::{PatternAst, PatternBuilder, PatternIter},
+    errors::Error,
+    force_graph::Initializer,
+    force_graph::simulated_plushie::{SimulatedPlushie, snapshot::Snapshot},
 };
 use graph_construction::HookParams;
 
+fn hook_params(pattern: &PatternAst) -> HookParams {
+    HookParams {
+        tip_from_fo: true,
+        enforce_counts: false,
+        handedness: pattern
+            .parts
+            .iter()
+            .map(|part| part.parameters.handedness)
+            .collect(),
+        inside_out: pattern
+            .parts
+            .iter()
+            .map(|part| part.parameters.inside_out)
+            .collect(),
+    }
+}
+
 pub fn parse(
     acl_source: &str,
     hook_size: f32,
     initializer: &Initializer,
 ) -> Result<(PlushieDef, SimulatedPlushie), Error> {
+    let (definition, part_limits) = build_definition(acl_source)?;
+    Ok((
+        definition.clone(),
+        SimulatedPlushie::from(definition, initializer, hook_size, &part_limits),
+    ))
+}
+
+/// Prepares to build the graph of `pattern` one action at a time, with the same settings as [`parse`].
+pub fn step_hook(pattern: &PatternAst) -> Result<HookStepper<PatternIter<'_>>, HookError> {
+    HookStepper::new(pattern.as_iter(), hook_params(pattern))
+}
+
+/// Like [`parse`], but keeps positions of the nodes that are built the same way as in the `previous` plushie.
+/// Also returns the number of kept nodes, nodes past that index are new.
+pub fn reparse(
+    acl_source: &str,
+    hook_size: f32,
+    initializer: &Initializer,
+    previous_definition: &PlushieDef,
+    previous: &SimulatedPlushie,
+) -> Result<(PlushieDef, SimulatedPlushie, usize), Error> {
+    let (definition, part_limits) = build_definition(acl_source)?;
+    let prefix = definition
+        .common_prefix(previous_definition)
+        .min(previous.nodes().len());
+    Ok((
+        definition.clone(),
+        SimulatedPlushie::from_prefix(
+            previous,
+            prefix,
+            definition,
+            initializer,
+            hook_size,
+            &part_limits,
+        ),
+        prefix,
+    ))
+}
+
+/// Builds only the definition of the plushie, e.g. to compare it with the one in simulation using [`PlushieDef::diff`].
+pub fn parse_definition(acl_source: &str) -> Result<PlushieDef, Error> {
+    build_definition(acl_source).map(|(definition, _)| definition)
+}
+
+/// Like [`parse`], but the plushie continues from `snapshot` instead of starting over.
+/// Fails if the snapshot was taken of a different pattern.
+pub fn resume(
+    acl_source: &str,
+    snapshot: &Snapshot,
+) -> Result<(PlushieDef, SimulatedPlushie), Error> {
+    snapshot
+        .check_pattern(acl_source)
+        .map_err(Error::Snapshot)?;
+    let (definition, part_limits) = build_definition(acl_source)?;
+    Ok((
+        definition.clone(),
+        SimulatedPlushie::resume(definition, &part_limits, snapshot).map_err(Error::Snapshot)?,
+    ))
+}
+
+fn build_definition(acl_source: &str) -> Result<(PlushieDef, Vec<usize>), Error> {
     let pattern = PatternBuilder::parse(acl_source).or_else(|e| Err(Error::Pattern(e)))?;
-    let hook_params = HookParams {
-        tip_from_fo: true,
-        enforce_counts: false,
-    };
-    let graph = graph_construction::parse(pattern.as_iter(), hook_params)
-        .or_else(|e| Err(Error::Hook(e)))?;
-    assert!(graph.nodes.len() == graph.edges.len());
+    pattern.analyze().map_err(Error::Pattern)?;
+    let graph =
+        graph_construction::parse(pattern.as_iter(), hook_params(&pattern)).map_err(Error::Hook)?;
 
-    let definition = PlushieDef {
+    let mut definition = PlushieDef {
         pattern,
         edges: graph.edges,
         nodes: graph.nodes,
         part_clusters: graph.part_joins,
         deferred_edges: graph.deferred_edges,
     };
-    Ok((
-        definition.clone(),
-        SimulatedPlushie::from(definition, initializer, hook_size, &graph.part_limits),
-    ))
+    // ids are named after the parts, so node part indices have to be checked first
+    definition.validate().map_err(Error::Invalid)?;
+    definition.assign_node_ids();
+    Ok((definition, graph.part_limits))
 }
 
+#[cfg(test)]
+mod fuzz;
 #[cfg(test)]
 mod tests;
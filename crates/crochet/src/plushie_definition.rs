@@ -1,6 +1,8 @@
+use std::collections::HashMap;
+
 use crate::{
-    acl::PatternAst,
-    data::{DeferredEdge, Edges, Node, PartClusters},
+    acl::{PatternAst, RepetitionPath},
+    data::{DeferredEdge, Edges, Node, NodeId, NodeIndex, PartClusters},
 };
 
 pub type ColorRgb = [u8; 3];
@@ -16,3 +18,320 @@ pub struct PlushieDef {
     pub part_clusters: PartClusters,
     pub deferred_edges: Vec<DeferredEdge>,
 }
+
+impl PlushieDef {
+    /// Gives every node its [`NodeId`], based on the part and the action that created it.
+    pub(crate) fn assign_node_ids(&mut self) {
+        let mut created: HashMap<(usize, RepetitionPath), usize> = HashMap::new();
+        for node in &mut self.nodes {
+            let path = &node.origin.path;
+            let index = created.entry((node.part_index, path.clone())).or_insert(0);
+            node.id = NodeId {
+                part: self.pattern.parts[node.part_index].name.clone(),
+                path: path.clone(),
+                index: *index,
+            };
+            *index += 1;
+        }
+    }
+
+    pub fn find_node(&self, id: &NodeId) -> Option<NodeIndex> {
+        self.nodes.iter().position(|node| &node.id == id)
+    }
+
+    /// For every node of `self`, index of the node with the same [`NodeId`] in `other`.
+    pub fn map_nodes_to(&self, other: &PlushieDef) -> Vec<Option<NodeIndex>> {
+        let other_ids: HashMap<&NodeId, NodeIndex> = other
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (&node.id, index))
+            .collect();
+        self.nodes
+            .iter()
+            .map(|node| other_ids.get(&node.id).copied())
+            .collect()
+    }
+
+    /// Number of leading nodes that are built the same way in both definitions,
+    /// i.e. they have the same links, peculiarity and part, and the part is worked with the same hand.
+    /// Colors may differ, they don't affect the simulation.
+    /// Sews are compared at the node they are performed with.
+    pub fn common_prefix(&self, other: &PlushieDef) -> usize {
+        let handedness = |definition: &PlushieDef, part: usize| {
+            definition
+                .pattern
+                .parts
+                .get(part)
+                .map(|part| part.parameters.handedness)
+        };
+        let mut prefix = self
+            .nodes
+            .iter()
+            .zip(&other.nodes)
+            .enumerate()
+            .take_while(|(i, (mine, their))| {
+                mine.peculiarity == their.peculiarity
+                    && mine.part_index == their.part_index
+                    && handedness(self, mine.part_index) == handedness(other, their.part_index)
+                    && same_links(
+                        self.edges.edges_from_node(*i),
+                        other.edges.edges_from_node(*i),
+                    )
+            })
+            .count();
+
+        let mut my_sews = self.deferred_edges.iter();
+        let mut their_sews = other.deferred_edges.iter();
+        loop {
+            match (my_sews.next(), their_sews.next()) {
+                (None, None) => break,
+                (Some(mine), Some(their)) if mine == their => continue,
+                (Some(sew), None) | (None, Some(sew)) => prefix = prefix.min(sew.with_node),
+                (Some(mine), Some(their)) => {
+                    prefix = prefix.min(mine.with_node).min(their.with_node)
+                }
+            }
+            break;
+        }
+        prefix
+    }
+
+    /// Tells whether a simulation of `self` can continue with `other` in place of `self`.
+    pub fn diff(&self, other: &PlushieDef) -> DefinitionDiff {
+        let same_parts = self.pattern.parts.len() == other.pattern.parts.len()
+            && self
+                .pattern
+                .parts
+                .iter()
+                .zip(&other.pattern.parts)
+                .all(|(mine, their)| {
+                    mine.name == their.name && mine.parameters == their.parameters
+                });
+        let same_topology = same_parts
+            && self.nodes.len() == other.nodes.len()
+            && self.deferred_edges == other.deferred_edges
+            && self.common_prefix(other) == self.nodes.len();
+        if !same_topology {
+            return DefinitionDiff::Topology;
+        }
+        DefinitionDiff::SameTopology {
+            recolored: self
+                .nodes
+                .iter()
+                .zip(&other.nodes)
+                .enumerate()
+                .filter(|(_, (mine, their))| mine.color != their.color)
+                .map(|(index, _)| index)
+                .collect(),
+        }
+    }
+}
+
+/// How two definitions of a plushie differ, see [`PlushieDef::diff`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DefinitionDiff {
+    /// Same graph and part parameters, the simulation can continue with the other definition.
+    /// Origins may differ (e.g. after editing comments), nodes in `recolored` changed color.
+    SameTopology { recolored: Vec<NodeIndex> },
+    /// The graph or part parameters changed, the plushie has to be rebuilt.
+    Topology,
+}
+
+fn same_links(a: &[usize], b: &[usize]) -> bool {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort();
+    b.sort();
+    a == b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{acl::Repeat, errors::Error, force_graph::Initializer};
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn definition(acl: &str) -> Result<PlushieDef, Error> {
+        crate::parse(acl, 1.0, &Initializer::OneByOne).map(|(def, _)| def)
+    }
+
+    #[test]
+    fn test_common_prefix_of_appended_rounds() {
+        let before = definition(indoc! {"
+            : MR(6)
+            : 6 inc
+        "})
+        .unwrap();
+        let after = definition(indoc! {"
+            : MR(6)
+            : 6 inc
+            : 12 sc
+            FO
+        "})
+        .unwrap();
+        assert_eq!(before.common_prefix(&after), before.nodes.len());
+        assert_eq!(after.common_prefix(&before), before.nodes.len());
+    }
+
+    #[test]
+    fn test_common_prefix_stops_at_changed_stitch() {
+        let before = definition(": MR(6)\n: 6 inc\n: 12 sc").unwrap();
+        let after = definition(": MR(6)\n: 6 inc\n: 2 sc, dec, 8 sc").unwrap();
+        // MR creates 7 nodes, inc creates 2 each
+        assert_eq!(before.common_prefix(&after), 7 + 12 + 2);
+    }
+
+    #[test]
+    fn test_common_prefix_ignores_comments_and_origins() {
+        let before = definition(": MR(6)\n: 6 inc").unwrap();
+        let after = definition("# the bottom\n: MR(6)\n\n: [inc] x 6").unwrap();
+        assert_eq!(before.common_prefix(&after), before.nodes.len());
+    }
+
+    #[test]
+    fn test_common_prefix_ignores_color_change() {
+        let before = definition(": MR(6)\n: 6 inc").unwrap();
+        let after = definition(": MR(6)\ncolor(255, 0, 0)\n: 6 inc").unwrap();
+        assert_eq!(before.common_prefix(&after), before.nodes.len());
+    }
+
+    #[test]
+    fn test_common_prefix_stops_at_part_with_other_handedness() {
+        let pattern = |arm_handedness: &str| {
+            definition(&format!(
+                "== Body ==\n: MR(6)\n: 6 inc\n== Arm ==\n{arm_handedness}\n: MR(6)\n: 6 sc"
+            ))
+            .unwrap()
+        };
+        let before = pattern("");
+        let after = pattern("@handedness = left");
+        assert_eq!(before.common_prefix(&after), 7 + 12);
+    }
+
+    #[test]
+    fn test_diff_of_cosmetic_changes() {
+        let before = definition(": MR(6)\n: 6 inc").unwrap();
+        let after = definition(indoc! {"
+            # the bottom
+            : MR(6)
+            color(255, 0, 0)
+            : [inc] x 6
+        "})
+        .unwrap();
+        assert_eq!(
+            before.diff(&after),
+            DefinitionDiff::SameTopology {
+                recolored: (7..19).collect()
+            }
+        );
+        assert_eq!(
+            before.diff(&before),
+            DefinitionDiff::SameTopology { recolored: vec![] }
+        );
+    }
+
+    #[test]
+    fn test_diff_of_topology_changes() {
+        let before = definition(": MR(6)\n: 6 inc").unwrap();
+        for changed in [
+            ": MR(6)\n: 5 inc, sc",
+            ": MR(6)\n: 6 inc\n: 12 sc",
+            "@centroids = 2\n: MR(6)\n: 6 inc",
+            ": MR(6)\n: 6 inc, mark(a)\ngoto(a)\nsew(a, R1.s1)",
+        ] {
+            assert_eq!(
+                before.diff(&definition(changed).unwrap()),
+                DefinitionDiff::Topology,
+                "{changed}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_common_prefix_stops_at_new_sew() {
+        let before = definition(indoc! {"
+            : MR(6)
+            : 6 sc, mark(a)
+            : 6 sc, mark(b)
+            : 6 sc
+        "})
+        .unwrap();
+        let after = definition(indoc! {"
+            : MR(6)
+            : 6 sc, mark(a)
+            : 6 sc, mark(b)
+            sew(a, b)
+            : 6 sc
+        "})
+        .unwrap();
+        assert_eq!(before.common_prefix(&after), 7 + 6 + 6);
+    }
+
+    #[test]
+    fn test_node_ids() {
+        let def = definition(indoc! {"
+            == Body ==
+            R1: MR(3)
+            R2: sc, [inc] x 2
+        "})
+        .unwrap();
+        // MR root and 3 ring stitches share the action
+        assert_eq!(def.nodes[3].id.index, 3);
+        assert_eq!(def.nodes[3].id.path.stitch, Some(1));
+        let second_inc = &def.nodes[4 + 1 + 2..4 + 1 + 4];
+        for (index, node) in second_inc.iter().enumerate() {
+            assert_eq!(node.id.part, "Body");
+            assert_eq!(
+                node.id.path,
+                RepetitionPath {
+                    round: Some(2),
+                    repeats: vec![Repeat { index: 2, of: 2 }],
+                    stitch: Some(4),
+                }
+            );
+            assert_eq!(node.id.index, index);
+        }
+    }
+
+    #[test]
+    fn test_node_ids_survive_edit_in_earlier_part() {
+        const BODY: &str = indoc! {"
+            == Body ==
+            R1: MR(6)
+            R2: 6 inc
+            R3: 12 sc
+            FO
+        "};
+        let before = definition(&format!("== Arm ==\nR1: MR(6)\nR2: 6 sc\n{BODY}")).unwrap();
+        let after = definition(&format!("== Arm ==\nR1: MR(6)\nR2: 6 inc\n{BODY}")).unwrap();
+        let mapping = before.map_nodes_to(&after);
+        let body_start = before.nodes.iter().position(|n| n.part_index == 1).unwrap();
+        // Arm got 6 nodes longer
+        for (index, mapped) in mapping.iter().enumerate().skip(body_start) {
+            assert_eq!(*mapped, Some(index + 6));
+        }
+        let tip = before.nodes.len() - 1;
+        assert_eq!(
+            after.find_node(&before.nodes[tip].id),
+            Some(after.nodes.len() - 1)
+        );
+    }
+
+    #[test]
+    fn test_node_ids_change_with_repetition() {
+        let before = definition(": MR(6)\n: 6 inc\n: 12 sc").unwrap();
+        let after = definition(": MR(6)\n: 6 inc\n: 11 sc, sc").unwrap();
+        let mapping = before.map_nodes_to(&after);
+        let r3_first = 7 + 12;
+        assert!(
+            mapping[..r3_first]
+                .iter()
+                .enumerate()
+                .all(|(i, m)| *m == Some(i))
+        );
+        // "12 sc" became "11 sc", which is another repetition
+        assert!(mapping[r3_first..].iter().all(|m| m.is_none()));
+    }
+}
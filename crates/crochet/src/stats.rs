@@ -0,0 +1,278 @@
+use crate::{
+    ColorRgb, PlushieDef,
+    acl::Action,
+    data::{EdgeKind, NodeIndex, Peculiarity},
+    force_graph::{link_force::LinkGauge, simulated_plushie::SimulatedPlushie},
+};
+
+/// Converts link lengths into yarn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gauge {
+    /// One hook size in metres, rest lengths of the links are given in hook sizes
+    pub hook_size: f32,
+    /// Rest length of every kind of link, as in the simulation
+    pub links: LinkGauge,
+    /// Metres of yarn used per metre of links, yarn wraps around the loops so it's well above 1
+    pub yarn_per_link: f32,
+}
+
+impl Default for Gauge {
+    /// Roughly worsted weight yarn on a 4 mm hook
+    fn default() -> Self {
+        Self {
+            hook_size: 0.005,
+            links: LinkGauge::default(),
+            yarn_per_link: 4.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternStats {
+    pub parts: Vec<PartStats>,
+    /// In the order the colors are first used
+    pub yarn: Vec<YarnUsage>,
+    /// Number of times the color differs from the previous stitch of the same part
+    pub color_changes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartStats {
+    pub name: String,
+    /// Stitches worked in the part. Magic ring roots and fasten off tips are not stitches.
+    pub stitches: usize,
+    pub rounds: Vec<RoundStats>,
+    pub increases: usize,
+    pub decreases: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundStats {
+    pub number: usize,
+    pub stitches: usize,
+    /// Count written at the end of the round, if any
+    pub declared_count: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct YarnUsage {
+    pub color: ColorRgb,
+    pub links: usize,
+    /// Estimated in metres. Yarn for sewing is not included.
+    pub length: f32,
+}
+
+impl PatternStats {
+    /// Assumes every link has the rest length of its kind.
+    pub fn from_definition(definition: &PlushieDef, gauge: &Gauge) -> Self {
+        Self::new(definition, gauge, |_, _, kind| {
+            gauge.links.link(kind).length
+        })
+    }
+
+    /// Measures links of the (ideally relaxed) plushie. Links of nodes that aren't simulated yet are assumed to be at rest.
+    pub fn from_simulation(
+        definition: &PlushieDef,
+        plushie: &SimulatedPlushie,
+        hook_size: f32,
+        gauge: &Gauge,
+    ) -> Self {
+        let nodes = plushie.nodes();
+        Self::new(definition, gauge, |a, b, kind| {
+            match (nodes.get(a), nodes.get(b)) {
+                (Some(a), Some(b)) => a.position.distance(b.position) / hook_size,
+                _ => gauge.links.link(kind).length,
+            }
+        })
+    }
+
+    /// `link_length` returns length of the link of the given kind between two nodes, in hook sizes
+    fn new(
+        definition: &PlushieDef,
+        gauge: &Gauge,
+        link_length: impl Fn(NodeIndex, NodeIndex, EdgeKind) -> f32,
+    ) -> Self {
+        let mut parts: Vec<PartStats> = definition
+            .pattern
+            .parts
+            .iter()
+            .map(|part| {
+                let count = |wanted: Action| {
+                    part.actions
+                        .iter()
+                        .filter(|action| action.action == wanted)
+                        .count()
+                };
+                PartStats {
+                    name: part.name.clone(),
+                    stitches: 0,
+                    rounds: part
+                        .rounds
+                        .iter()
+                        .map(|round| RoundStats {
+                            number: round.number,
+                            stitches: 0,
+                            declared_count: round.declared_count,
+                        })
+                        .collect(),
+                    increases: count(Action::Inc),
+                    decreases: count(Action::Dec),
+                }
+            })
+            .collect();
+
+        let mut color_changes = 0;
+        let mut yarn: Vec<YarnUsage> = vec![];
+        for (index, node) in definition.nodes.iter().enumerate() {
+            if let Some(previous) = index.checked_sub(1).map(|i| &definition.nodes[i])
+                && previous.part_index == node.part_index
+                && previous.color != node.color
+            {
+                color_changes += 1;
+            }
+
+            let position = match yarn.iter().position(|usage| usage.color == node.color) {
+                Some(position) => position,
+                None => {
+                    yarn.push(YarnUsage {
+                        color: node.color,
+                        links: 0,
+                        length: 0.0,
+                    });
+                    yarn.len() - 1
+                }
+            };
+            let usage = &mut yarn[position];
+            for &target in definition.edges.edges_from_node(index) {
+                usage.links += 1;
+                let kind = EdgeKind::of(&definition.nodes, index, target);
+                usage.length +=
+                    link_length(index, target, kind) * gauge.hook_size * gauge.yarn_per_link;
+            }
+
+            if matches!(
+                node.peculiarity,
+                Some(Peculiarity::Locked | Peculiarity::Tip)
+            ) {
+                continue;
+            }
+            let part = &mut parts[node.part_index];
+            part.stitches += 1;
+            if let Some(round) = node.origin.path.round
+                && let Some(round) = part.rounds.iter_mut().find(|r| r.number == round)
+            {
+                round.stitches += 1;
+            }
+        }
+
+        Self {
+            parts,
+            yarn,
+            color_changes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::force_graph::{Initializer, link_force::LinkParams};
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const PATTERN: &str = indoc! {"
+        == Ball ==
+        color(255, 0, 0)
+        R1: MR(6)
+        R2: 6 inc (12)
+        color(0, 0, 255)
+        R3: [sc, dec] x 4 (8)
+        FO
+    "};
+
+    #[test]
+    fn test_counts() {
+        let (definition, _) = crate::parse(PATTERN, 1.0, &Initializer::OneByOne).unwrap();
+        let stats = PatternStats::from_definition(&definition, &Gauge::default());
+        assert_eq!(stats.parts.len(), 1);
+        let ball = &stats.parts[0];
+        assert_eq!(ball.name, "Ball");
+        assert_eq!(ball.stitches, 6 + 12 + 8);
+        assert_eq!(ball.increases, 6);
+        assert_eq!(ball.decreases, 4);
+        assert_eq!(
+            ball.rounds,
+            vec![
+                RoundStats {
+                    number: 1,
+                    stitches: 6,
+                    declared_count: None
+                },
+                RoundStats {
+                    number: 2,
+                    stitches: 12,
+                    declared_count: Some(12)
+                },
+                RoundStats {
+                    number: 3,
+                    stitches: 8,
+                    declared_count: Some(8)
+                },
+            ]
+        );
+        assert_eq!(stats.color_changes, 1);
+        let colors: Vec<ColorRgb> = stats.yarn.iter().map(|usage| usage.color).collect();
+        assert_eq!(colors, vec![[255, 0, 0], [0, 0, 255]]);
+    }
+
+    #[test]
+    fn test_yarn_length() {
+        let (definition, plushie) =
+            crate::parse(PATTERN, 1.0, &Initializer::RegularCylinder(12)).unwrap();
+        let gauge = Gauge {
+            hook_size: 0.01,
+            links: LinkGauge::UNIFORM,
+            yarn_per_link: 2.0,
+        };
+        let stats = PatternStats::from_definition(&definition, &gauge);
+        let links: usize = definition.edges.iter().map(|links| links.len()).sum();
+        assert_eq!(
+            stats.yarn.iter().map(|usage| usage.links).sum::<usize>(),
+            links
+        );
+        for usage in &stats.yarn {
+            assert!((usage.length - usage.links as f32 * 0.02).abs() < 1e-4);
+        }
+
+        // anchors twice as long as the rest
+        let long_anchors = Gauge {
+            links: LinkGauge {
+                anchor: LinkParams {
+                    length: 2.0,
+                    stiffness: 1.0,
+                },
+                ..LinkGauge::UNIFORM
+            },
+            ..gauge
+        };
+        let anchors = (0..definition.nodes.len())
+            .flat_map(|i| {
+                definition
+                    .edges
+                    .edges_from_node(i)
+                    .iter()
+                    .map(move |&j| (i, j))
+            })
+            .filter(|&(i, j)| EdgeKind::of(&definition.nodes, i, j) == EdgeKind::Anchor)
+            .count();
+        assert!(anchors > 0);
+        let total = |stats: &PatternStats| stats.yarn.iter().map(|usage| usage.length).sum::<f32>();
+        let longer = PatternStats::from_definition(&definition, &long_anchors);
+        assert!((total(&longer) - total(&stats) - anchors as f32 * 0.02).abs() < 1e-4);
+
+        // cylinder is not relaxed, its links are stretched and squashed
+        let measured = PatternStats::from_simulation(&definition, &plushie, 1.0, &gauge);
+        assert_eq!(measured.parts, stats.parts);
+        assert_ne!(measured.yarn, stats.yarn);
+    }
+}
@@ -1,8 +1,13 @@
 use indoc::indoc;
 
 use crate::{
-    PlushieDef, acl::Action, errors::Error, force_graph::simulated_plushie::SimulatedPlushie,
-    graph_construction::ErrorCode, parse,
+    PlushieDef,
+    acl::{Action, PatternErrorCode},
+    data::Peculiarity,
+    errors::Error,
+    force_graph::{simulated_plushie::SimulatedPlushie, single_loop::single_loop_forces},
+    graph_construction::ErrorCode,
+    parse,
 };
 use pretty_assertions::assert_eq;
 
@@ -65,11 +70,11 @@ fn test_first_stitch_must_be_mr() {
         : sc
     "};
     let err = default_parse(acl).unwrap_err();
-    let Error::Hook(err) = err else {
+    let Error::Pattern(err) = err else {
         panic!();
     };
-    assert_eq!(err.code, ErrorCode::BadStarter);
-    assert_eq!(&acl[err.origin.unwrap().as_range()], "sc");
+    assert_eq!(err.code, PatternErrorCode::BadStarter);
+    assert_eq!(&acl[err.origin.as_range()], "sc");
 
     let acl = indoc! {"
         == Part ==
@@ -81,11 +86,11 @@ fn test_first_stitch_must_be_mr() {
         : sc
     "};
     let err = default_parse(acl).unwrap_err();
-    let Error::Hook(err) = err else {
+    let Error::Pattern(err) = err else {
         panic!();
     };
-    assert_eq!(err.code, ErrorCode::BadStarter);
-    assert_eq!(&acl[err.origin.unwrap().as_range()], "sc");
+    assert_eq!(err.code, PatternErrorCode::BadStarter);
+    assert_eq!(&acl[err.origin.as_range()], "sc");
 }
 
 #[test]
@@ -126,3 +131,166 @@ fn test_two_parts() {
     assert_eq!(plushie_def.pattern.parts[0].name, "Part1");
     assert_eq!(plushie_def.pattern.parts[1].name, "Part2");
 }
+
+#[test]
+fn test_sew_with_address_matches_sew_with_marks() {
+    let with_marks = indoc! {"
+        == Part1 ==
+        : MR(6)
+        : 2 sc, mark(p1), 4 sc
+        FO
+
+        == Part2 ==
+        : MR(6)
+        : 6 sc, mark(p2)
+        FO
+
+        sew(p1, p2)
+    "};
+    let with_addresses = indoc! {"
+        == Part1 ==
+        : MR(6)
+        : 6 sc
+        FO
+
+        == Part2 ==
+        : MR(6)
+        : 6 sc
+        FO
+
+        sew(Part1.R2.s2, R2.s6)
+    "};
+    let (marked, _) = default_parse(with_marks).unwrap();
+    let (addressed, _) = default_parse(with_addresses).unwrap();
+    assert_eq!(marked.edges, addressed.edges);
+    assert_eq!(marked.deferred_edges.len(), 1);
+    assert_eq!(addressed.deferred_edges.len(), 1);
+    assert_eq!(
+        marked.deferred_edges[0].node_a,
+        addressed.deferred_edges[0].node_a
+    );
+    assert_eq!(
+        marked.deferred_edges[0].node_b,
+        addressed.deferred_edges[0].node_b
+    );
+}
+
+#[test]
+fn test_hook_error_reports_repetition_path() {
+    let acl = indoc! {"
+        : MR(6)
+        : [sc, dec] x 3
+        : [dec] x 3
+    "};
+    let err = default_parse(acl).unwrap_err();
+    let Error::Hook(err) = err else {
+        panic!();
+    };
+    assert_eq!(err.code, ErrorCode::NoAnchorToPullThrough);
+    assert_eq!(err.path.to_string(), "R3, repeat 3 of 3, stitch 3");
+}
+
+#[test]
+fn test_sew_to_ring_stitch() {
+    let acl = indoc! {"
+        == Body ==
+        R1: MR(6)
+        R2: 6 inc
+        FO
+        sew(R1.s2, R2.s12)
+        sew(R1.s5, R2.s1)
+        sew(R1.s6, R2.s7)
+    "};
+    let (definition, _) = default_parse(acl).unwrap();
+    let sewn: Vec<(usize, usize)> = definition
+        .deferred_edges
+        .iter()
+        .map(|edge| (edge.node_a, edge.node_b))
+        .collect();
+    // ring root is node 0, every inc of R2 creates 2 nodes, each of them has its own address
+    assert_eq!(sewn, vec![(2, 6 + 12), (5, 6 + 1), (6, 6 + 7)]);
+}
+
+#[test]
+fn test_goto_first_half_of_inc() {
+    let acl = indoc! {"
+        : MR(6)
+        : [sc, inc] x 3 (9)
+        : 9 sc
+        goto(R2.s8)
+        : 2 sc
+    "};
+    let (definition, _) = default_parse(acl).unwrap();
+    // R2.s8 is the first half of the last inc, node 14, worked into the last ring stitch
+    let first_half = 6 + 1 + 7;
+    let after_goto = definition.nodes.len() - 2;
+    let mut links = definition.edges.edges_from_node(after_goto).to_vec();
+    links.sort();
+    // continues from the first half, into the anchor of the inc
+    assert_eq!(links, vec![6, first_half]);
+}
+
+#[test]
+fn test_sew_to_same_node_is_an_error() {
+    let acl = indoc! {"
+        : MR(6)
+        mark(a)
+        sew(a, R1.s6)
+    "};
+    let Error::Hook(err) = default_parse(acl).unwrap_err() else {
+        panic!();
+    };
+    assert_eq!(err.code, ErrorCode::SewToSameNode);
+}
+
+#[test]
+fn test_left_handed_plushie_is_mirrored() {
+    let pattern = indoc! {"
+        : MR(6)
+        : 6 inc
+        : FLO, 12 sc
+        : BLO, 12 sc
+    "};
+    let (right_def, right) = default_parse(pattern).unwrap();
+    let (left_def, left) = default_parse(&format!("@handedness = left\n{pattern}")).unwrap();
+    assert_eq!(right_def.edges, left_def.edges);
+
+    let mirror = |v: glam::Vec3| glam::Vec3::new(v.x, v.y, -v.z);
+    let mut right_push = vec![glam::Vec3::ZERO; right.nodes().len()];
+    let mut left_push = vec![glam::Vec3::ZERO; left.nodes().len()];
+    single_loop_forces(right.nodes(), 1.0, &mut right_push);
+    single_loop_forces(left.nodes(), 1.0, &mut left_push);
+    assert!(right_push.iter().any(|push| push.length() > 0.5));
+    for i in 0..right.nodes().len() {
+        assert_eq!(left.nodes()[i].position, mirror(right.nodes()[i].position));
+        // the loops are pushed to the same side of the mirrored fabric
+        assert!(left_push[i].distance(mirror(right_push[i])) < 1e-5);
+    }
+}
+
+#[test]
+fn test_inside_out_part_pushes_loops_to_the_other_side() {
+    let pattern = indoc! {"
+        : MR(6)
+        : 6 inc
+        : FLO, 12 sc
+    "};
+    let (_, plushie) = default_parse(pattern).unwrap();
+    let (inside_out_def, inside_out) =
+        default_parse(&format!("@inside_out = yes\n{pattern}")).unwrap();
+    assert!(inside_out_def.nodes.iter().all(|node| node.inside_out));
+    assert!(matches!(
+        inside_out_def.nodes.last().unwrap().outside_peculiarity(),
+        Some(Peculiarity::BLO(_))
+    ));
+
+    let mut push = vec![glam::Vec3::ZERO; plushie.nodes().len()];
+    let mut inside_out_push = vec![glam::Vec3::ZERO; inside_out.nodes().len()];
+    single_loop_forces(plushie.nodes(), 1.0, &mut push);
+    single_loop_forces(inside_out.nodes(), 1.0, &mut inside_out_push);
+    assert!(push.iter().any(|push| push.length() > 0.5));
+    assert_eq!(
+        inside_out_push,
+        push.iter().map(|push| -*push).collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,384 @@
+use std::fmt::Display;
+
+use crate::{
+    PlushieDef,
+    acl::Origin,
+    data::{NodeIndex, Peculiarity},
+};
+
+/// Links built by the hook that a single node can have before the simulation misbehaves.
+/// Sews don't count, a stitch may be sewn to any number of parts.
+/// Magic ring roots (the [`Peculiarity::Locked`] nodes) are exempt, they link to every stitch of the ring.
+pub const MAX_LINKS_PER_NODE: usize = 16;
+
+#[derive(Debug)]
+pub struct Error {
+    pub code: ErrorCode,
+    /// Origin of the offending node, if the error can be attributed to one
+    pub origin: Option<Origin>,
+}
+
+/// Broken structural invariant of a [`PlushieDef`]. Any of these means a bug in the crate.
+#[derive(Debug, PartialEq)]
+pub enum ErrorCode {
+    NodeCountMismatch {
+        nodes: usize,
+        edges: usize,
+    },
+    /// Every link is expected to be stored on the node with the higher index
+    UnorderedLink {
+        node: NodeIndex,
+        target: NodeIndex,
+    },
+    TooManyLinks {
+        node: NodeIndex,
+        links: usize,
+    },
+    DanglingParent {
+        node: NodeIndex,
+        parent: NodeIndex,
+    },
+    PushPlaneOutOfRange {
+        node: NodeIndex,
+        point: NodeIndex,
+    },
+    DeferredEdgeOutOfRange {
+        node_a: NodeIndex,
+        node_b: NodeIndex,
+        with_node: NodeIndex,
+    },
+    PartIndexOutOfRange {
+        node: NodeIndex,
+        part_index: usize,
+    },
+    PartJoinOutOfRange {
+        from: usize,
+        to: usize,
+    },
+    /// Node can't be reached from the first node of its part
+    DisconnectedNode {
+        node: NodeIndex,
+        part_index: usize,
+    },
+}
+
+impl PlushieDef {
+    /// Checks the structural invariants of the graph.
+    pub fn validate(&self) -> Result<(), Error> {
+        use ErrorCode::*;
+        let nodes = self.nodes.len();
+        let parts = self.pattern.parts.len();
+
+        if nodes != self.edges.len() {
+            return Err(self.error(
+                NodeCountMismatch {
+                    nodes,
+                    edges: self.edges.len(),
+                },
+                None,
+            ));
+        }
+
+        let mut links = vec![0; nodes];
+        for (node, targets) in self.edges.iter().enumerate() {
+            for &target in targets {
+                if target >= node {
+                    return Err(self.error(UnorderedLink { node, target }, Some(node)));
+                }
+                links[node] += 1;
+                links[target] += 1;
+            }
+        }
+        for edge in &self.deferred_edges {
+            if edge.node_a >= nodes || edge.node_b >= nodes || edge.with_node > nodes {
+                return Err(self.error(
+                    DeferredEdgeOutOfRange {
+                        node_a: edge.node_a,
+                        node_b: edge.node_b,
+                        with_node: edge.with_node,
+                    },
+                    None,
+                ));
+            }
+        }
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            if links[index] > MAX_LINKS_PER_NODE && node.peculiarity != Some(Peculiarity::Locked) {
+                return Err(self.error(
+                    TooManyLinks {
+                        node: index,
+                        links: links[index],
+                    },
+                    Some(index),
+                ));
+            }
+            if let Some(parent) = node.parent
+                && parent >= index
+            {
+                return Err(self.error(
+                    DanglingParent {
+                        node: index,
+                        parent,
+                    },
+                    Some(index),
+                ));
+            }
+            if let Some(Peculiarity::BLO(points) | Peculiarity::FLO(points)) = node.peculiarity {
+                let (father, mother, grandparent) = points;
+                for point in [father, mother, grandparent] {
+                    if point >= nodes {
+                        return Err(
+                            self.error(PushPlaneOutOfRange { node: index, point }, Some(index))
+                        );
+                    }
+                }
+            }
+            if node.part_index >= parts {
+                return Err(self.error(
+                    PartIndexOutOfRange {
+                        node: index,
+                        part_index: node.part_index,
+                    },
+                    Some(index),
+                ));
+            }
+        }
+
+        for join in self.part_clusters.joins() {
+            if join.from >= parts || join.to >= parts {
+                return Err(self.error(
+                    PartJoinOutOfRange {
+                        from: join.from,
+                        to: join.to,
+                    },
+                    None,
+                ));
+            }
+        }
+
+        self.validate_connectivity()
+    }
+
+    fn validate_connectivity(&self) -> Result<(), Error> {
+        let mut components = Components::new(self.nodes.len());
+        for (node, targets) in self.edges.iter().enumerate() {
+            for &target in targets {
+                components.union(node, target);
+            }
+        }
+        for edge in &self.deferred_edges {
+            components.union(edge.node_a, edge.node_b);
+        }
+
+        let mut part_starts: Vec<Option<NodeIndex>> = vec![None; self.pattern.parts.len()];
+        for (index, node) in self.nodes.iter().enumerate() {
+            let start = *part_starts[node.part_index].get_or_insert(index);
+            if components.find(start) != components.find(index) {
+                return Err(self.error(
+                    ErrorCode::DisconnectedNode {
+                        node: index,
+                        part_index: node.part_index,
+                    },
+                    Some(index),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn error(&self, code: ErrorCode, node: Option<NodeIndex>) -> Error {
+        Error {
+            code,
+            origin: node.and_then(|node| self.nodes[node].origin.origin),
+        }
+    }
+}
+
+/// Union-find over node indexes
+struct Components {
+    parent: Vec<usize>,
+}
+
+impl Components {
+    fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, mut node: usize) -> usize {
+        while self.parent[node] != node {
+            self.parent[node] = self.parent[self.parent[node]];
+            node = self.parent[node];
+        }
+        node
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let a = self.find(a);
+        let b = self.find(b);
+        self.parent[a.max(b)] = a.min(b);
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.code)?;
+        if let Some(origin) = self.origin {
+            write!(
+                f,
+                " at node that originated at bytes {}..{}",
+                origin.as_range().start,
+                origin.as_range().end
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data::DeferredEdge, force_graph::Initializer};
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn definition(acl: &str) -> PlushieDef {
+        crate::parse(acl, 1.0, &Initializer::OneByOne).unwrap().0
+    }
+
+    const PATTERN: &str = indoc! {"
+        == Body ==
+        : MR(6)
+        : FLO, 6 inc, mark(a)
+        : 12 sc
+        FO
+
+        == Arm ==
+        : MR(4)
+        : 4 sc, mark(b)
+        sew(a, b)
+    "};
+
+    #[test]
+    fn test_valid_pattern() {
+        assert!(definition(PATTERN).validate().is_ok());
+    }
+
+    #[test]
+    fn test_node_count_mismatch() {
+        let mut def = definition(PATTERN);
+        def.nodes.pop();
+        assert_eq!(
+            def.validate().unwrap_err().code,
+            ErrorCode::NodeCountMismatch {
+                nodes: def.edges.len() - 1,
+                edges: def.edges.len()
+            }
+        );
+    }
+
+    #[test]
+    fn test_dangling_parent() {
+        let mut def = definition(PATTERN);
+        def.nodes[3].parent = Some(10);
+        let err = def.validate().unwrap_err();
+        assert_eq!(
+            err.code,
+            ErrorCode::DanglingParent {
+                node: 3,
+                parent: 10
+            }
+        );
+        assert_eq!(&PATTERN[err.origin.unwrap().as_range()], "MR");
+    }
+
+    #[test]
+    fn test_push_plane_out_of_range() {
+        let mut def = definition(PATTERN);
+        let node = def
+            .nodes
+            .iter()
+            .position(|n| matches!(n.peculiarity, Some(Peculiarity::FLO(_))))
+            .unwrap();
+        def.nodes[node].peculiarity = Some(Peculiarity::FLO((node + 1, node, 1000)));
+        assert_eq!(
+            def.validate().unwrap_err().code,
+            ErrorCode::PushPlaneOutOfRange { node, point: 1000 }
+        );
+    }
+
+    #[test]
+    fn test_too_many_links() {
+        let mut def = definition(PATTERN);
+        for _ in 0..MAX_LINKS_PER_NODE {
+            def.edges.link(10, 11);
+        }
+        assert!(matches!(
+            def.validate().unwrap_err().code,
+            ErrorCode::TooManyLinks { node: 10, .. }
+        ));
+    }
+
+    #[test]
+    fn test_many_parts_sewn_to_one_stitch() {
+        let mut pattern = String::from("== Body ==\n: MR(6)\n: 6 inc, mark(a)\n");
+        for part in 0..MAX_LINKS_PER_NODE {
+            pattern +=
+                &format!("== Arm{part} ==\n: MR(4)\n: 4 sc, mark(arm{part})\nsew(a, arm{part})\n");
+        }
+        let def = definition(&pattern);
+        assert_eq!(def.deferred_edges.len(), MAX_LINKS_PER_NODE);
+        assert!(def.validate().is_ok());
+    }
+
+    #[test]
+    fn test_deferred_edge_out_of_range() {
+        let mut def = definition(PATTERN);
+        def.deferred_edges.push(DeferredEdge {
+            with_node: 0,
+            node_a: 0,
+            node_b: def.nodes.len(),
+        });
+        assert!(matches!(
+            def.validate().unwrap_err().code,
+            ErrorCode::DeferredEdgeOutOfRange { .. }
+        ));
+    }
+
+    #[test]
+    fn test_part_index_out_of_range() {
+        let mut def = definition(PATTERN);
+        let last = def.nodes.len() - 1;
+        def.nodes[last].part_index = 2;
+        assert_eq!(
+            def.validate().unwrap_err().code,
+            ErrorCode::PartIndexOutOfRange {
+                node: last,
+                part_index: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_node_disconnected_from_its_part() {
+        let mut def = definition(PATTERN);
+        // move the whole Arm into Body, without the sew it's a separate component
+        for node in &mut def.nodes {
+            node.part_index = 0;
+        }
+        def.deferred_edges.clear();
+        let arm_start = def
+            .nodes
+            .iter()
+            .rposition(|n| n.peculiarity == Some(Peculiarity::Locked))
+            .unwrap();
+        assert_eq!(
+            def.validate().unwrap_err().code,
+            ErrorCode::DisconnectedNode {
+                node: arm_start,
+                part_index: 0
+            }
+        );
+    }
+}
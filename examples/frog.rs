@@ -1,4 +1,5 @@
 use bevy::utils::default;
+use crochet::force_graph::self_collision::CollisionScope;
 use crocheteer::project::{DisplayMode, Project, SimulationState};
 
 fn main() {
@@ -9,9 +10,6 @@ fn main() {
         // frog body need to be built down, not up
         // eyes need to be built at an angle, and with some more sensible initial position
         // same applies to limbs
-        // TODO stuffing between parts
-        // eyes phase into the body as the stuffing does not work between parts
-        // make repulsion act between parts (only when they are joined), keep centroid recalculation part-local
         // TODO ergonomic sews
         // place one mark (call it center) use one sew-like action, and let simulation decide which specific nodes to link
         // plushie needs to be relaxed for this to make sense,
@@ -111,6 +109,8 @@ fn main() {
             single_loop_force: 0.0,
             display_mode: DisplayMode::Pattern,
             initializer: crochet::force_graph::Initializer::OneByOne,
+            self_collision: Some(CollisionScope::Cluster),
+            snapshot: Some("frog.snapshot".into()),
             ..default()
         },
     };
@@ -27,6 +27,8 @@ use crate::{
 // a yarn I work with 5mm hook generally yields 5mm big stitches
 // TODO define in pattern? define in project settings? do we support variable hook size? if we support, is it variable per part or can it change in arbitrary positions?
 const HOOK_SIZE: f32 = 5e-4;
+/// Height of the infinite grid, the plushie lands on it when the floor is on
+const FLOOR: f32 = 0.0;
 const FIXED_UPDATE_BASE_HZ: f64 = 64.0;
 
 pub fn app(project: Project) -> App {
@@ -1,7 +1,8 @@
 use bevy::{platform::collections::HashMap, prelude::*};
+use crochet::force_graph::{gravity::Gravity, self_collision::SelfCollision};
 
 use crate::{
-    HOOK_SIZE,
+    FLOOR, HOOK_SIZE,
     plushie::{
         animation::data::Centroid,
         data::{Link, PlushieAssets},
@@ -19,12 +20,23 @@ pub fn simulation_step(
     assets: Res<PlushieAssets>,
     links: Query<&mut Link>,
 ) {
-    plushie.plushie.step(
+    let stats = plushie.plushie.step(
         &crochet::force_graph::simulated_plushie::step::SimulationParams {
             force_multiplier: 0.0003 * params.force_multiplier,
             single_loop_force: params.single_loop_force,
+            gauge: params.gauge,
+            integrator: params.integrator,
+            self_collision: params.self_collision.map(|scope| SelfCollision {
+                scope,
+                force: params.self_collision_force,
+            }),
+            gravity: params.gravity.map(|strength| Gravity { strength }),
+            floor: params.floor.then_some(FLOOR),
+            max_timestep: 4.0,
+            max_travel: 0.5,
         },
     );
+    plushie.stats.push(stats);
 
     // these blocks in braces could be their own systems
 
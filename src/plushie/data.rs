@@ -14,6 +14,8 @@ pub struct GraphNode {
     pub child_selection_indicator: Entity,
     pub child_per_display_mode: enum_map::EnumMap<DisplayMode, Entity>,
     pub origin: Option<crochet::acl::Origin>,
+    /// Distinguishes nodes that share the origin because of repetitions.
+    pub path: crochet::acl::RepetitionPath,
     pub part_index: usize,
 }
 
@@ -78,6 +80,7 @@ pub struct AddGraphNode {
     pub color: ColorRgb,
     pub peculiarity: Option<crochet::data::Peculiarity>,
     pub origin: Option<crochet::acl::Origin>,
+    pub path: crochet::acl::RepetitionPath,
     pub node_index: usize,
     pub part_index: usize,
 }
@@ -13,7 +13,7 @@ use crate::{
         display_mode::{set_display_mode, setup_display_modes},
         mouse_interactions::{deselect_on_empty_press, stop_dragging, update_dragging},
         shaders::{LinkMaterial, sync_shader_buffer},
-        spawning::ordered_plushie_build,
+        spawning::{hot_swap_cosmetic_changes, ordered_plushie_build},
         systems::{highlight_selected_nodes_visually, setup_assets},
     },
     state::{editor_simulation_sync::EditorSimulationSync, simulated_plushie::PlushieInSimulation},
@@ -24,6 +24,7 @@ use crate::{
 };
 use bevy::prelude::*;
 use data::*;
+use std::collections::BTreeMap;
 
 pub use display_mode::{DisplayMode, SetDisplayMode};
 
@@ -50,6 +51,7 @@ impl Plugin for PlushiePlugin {
             Update,
             (
                 build_plushie_from_pattern.run_if(ordered_plushie_build),
+                hot_swap_cosmetic_changes.run_if(resource_exists::<PlushieInSimulation>),
                 highlight_selected_nodes_in_pattern,
             )
                 .chain(),
@@ -78,6 +80,7 @@ pub fn highlight_selected_nodes_in_pattern(
         code_editor
             .highlighter
             .clear(HighlightLayer::LightBackground);
+        code_editor.repetition_labels.clear();
         return;
     }
     if added_selected.is_empty() && removed_selected.is_empty() && *was_in_sync {
@@ -85,6 +88,36 @@ pub fn highlight_selected_nodes_in_pattern(
     }
     *was_in_sync = true;
 
+    // the highlight covers every repetition of the token, the label tells which ones are selected
+    let mut repetitions: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    for node in &selected {
+        let Some(origin) = node.origin else {
+            continue;
+        };
+        if node.path.repeats.is_empty() {
+            continue;
+        }
+        let repetition = node
+            .path
+            .repeats
+            .iter()
+            .map(|repeat| format!("{}/{}", repeat.index, repeat.of))
+            .collect::<Vec<_>>()
+            .join("·");
+        repetitions
+            .entry(origin.as_range().start)
+            .or_default()
+            .push(repetition);
+    }
+    code_editor.repetition_labels = repetitions
+        .into_iter()
+        .map(|(start, mut labels)| {
+            labels.sort();
+            labels.dedup();
+            (start, labels.join(", "))
+        })
+        .collect();
+
     code_editor.highlighter.set(
         HighlightLayer::LightBackground,
         selected
@@ -1,6 +1,11 @@
+use std::collections::HashSet;
+use std::path::Path;
+
 use bevy::prelude::*;
 use crochet::ColorRgb;
+use crochet::force_graph::simulated_plushie::SimulatedPlushie;
 use crochet::force_graph::simulated_plushie::init::OneByOneResult;
+use crochet::force_graph::simulated_plushie::snapshot::Snapshot;
 use enum_map::enum_map;
 
 use crate::HOOK_SIZE;
@@ -79,6 +84,7 @@ fn add_graph_node(
                 child_per_display_mode,
                 child_selection_indicator,
                 origin: msg.origin,
+                path: msg.path.clone(),
                 part_index: msg.part_index,
             },
             Name::new("GraphNode"),
@@ -137,16 +143,105 @@ fn add_link_between(
         .add_children(&[standard_material_child, shader_material_child]);
 }
 
+/// Gives the `recolored` nodes and their links the current pattern color of the nodes
+fn recolor_nodes(
+    commands: &mut Commands,
+    plushie: &PlushieInSimulation,
+    recolored: &HashSet<usize>,
+    links: &Query<&Link>,
+    graph_nodes: &Query<&mut GraphNode>,
+    assets: &mut PlushieAssets,
+    materials: &mut Assets<StandardMaterial>,
+) {
+    let mut material = |index: usize| {
+        let color = plushie.plushie.nodes()[index].definition.color;
+        MeshMaterial3d(assets.get_or_create_fabric_material(color, materials))
+    };
+    for index in recolored {
+        let Some(entity) = plushie.node_lookup.index_to_entity.get(index) else {
+            continue;
+        };
+        if let Ok(graph_node) = graph_nodes.get(*entity) {
+            commands
+                .entity(graph_node.child_per_display_mode[DisplayMode::Pattern])
+                .insert(material(*index));
+        }
+    }
+    // links take the color of the later node
+    for link in links {
+        let later = [link.node_a, link.node_b]
+            .iter()
+            .filter_map(|node| plushie.node_lookup.entity_to_index.get(node))
+            .max();
+        if let Some(later) = later.filter(|later| recolored.contains(later)) {
+            commands
+                .entity(link.child_per_display_mode[DisplayMode::Pattern])
+                .insert(material(*later));
+        }
+    }
+}
+
+/// Takes edits that keep the graph as it is (comments, colors, "3 sc" in place of "sc, sc, sc") into the running simulation,
+/// without rebuilding the plushie.
+pub fn hot_swap_cosmetic_changes(
+    mut commands: Commands,
+    mut plushie: ResMut<PlushieInSimulation>,
+    mut sync_state: ResMut<EditorSimulationSync>,
+    code_editor: Res<CodeEditorState>,
+    mut assets: ResMut<PlushieAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut graph_nodes: Query<&mut GraphNode>,
+    links: Query<&Link>,
+    mut last_checked: Local<String>,
+) {
+    // the hook is rerun only once for every edit
+    if sync_state.in_sync || *last_checked == code_editor.code {
+        return;
+    }
+    last_checked.clone_from(&code_editor.code);
+    let Ok(definition) = crochet::parse_definition(&code_editor.code) else {
+        return;
+    };
+    let crochet::DefinitionDiff::SameTopology { recolored } = plushie.definition.diff(&definition)
+    else {
+        return;
+    };
+
+    plushie.plushie.swap_definition(&definition);
+    for (index, entity) in &plushie.node_lookup.index_to_entity {
+        if let Ok(mut graph_node) = graph_nodes.get_mut(*entity) {
+            let origin = &definition.nodes[*index].origin;
+            graph_node.origin = origin.origin;
+            graph_node.path = origin.path.clone();
+        }
+    }
+    recolor_nodes(
+        &mut commands,
+        &plushie,
+        &recolored.into_iter().collect(),
+        &links,
+        &graph_nodes,
+        &mut assets,
+        &mut materials,
+    );
+    plushie.definition = definition;
+    sync_state.plushie_parsed(code_editor.code.clone());
+}
+
 pub fn ordered_plushie_build(mut msgr: MessageReader<BuildPlushieFromPattern>) -> bool {
     msgr.read().last().is_some()
 }
 
+/// Despawns everything except the nodes in `kept_nodes`. Links are always respawned.
 fn despawn_old_plushie(
     commands: &mut Commands,
     existing_plushie_entities: Query<Entity, Or<(With<GraphNode>, With<Link>, With<Centroid>)>>,
+    kept_nodes: &NodeLookup,
 ) {
     for entity in existing_plushie_entities {
-        commands.entity(entity).despawn();
+        if !kept_nodes.entity_to_index.contains_key(&entity) {
+            commands.entity(entity).despawn();
+        }
     }
 }
 
@@ -173,37 +268,87 @@ pub fn build_plushie_from_pattern(
     mut state: ResMut<SimulationState>,
     display_presets: Res<DisplayPresets>,
     pipe: Res<ConsolePipe>,
+    previous_plushie: Option<Res<PlushieInSimulation>>,
     existing_plushie_entities: Query<Entity, Or<(With<GraphNode>, With<Link>, With<Centroid>)>>,
+    mut graph_nodes: Query<&mut GraphNode>,
 ) -> Result {
     let Some(msg) = msgr.read().last() else {
         return Ok(());
     };
 
-    despawn_old_plushie(&mut commands, existing_plushie_entities);
-    let (plushie_def, simulated_plushie) =
-        match crochet::parse(&msg.acl, HOOK_SIZE, &state.initializer) {
-            Ok(x) => x,
-            Err(err) => {
-                report_error(err, &mut code_editor.highlighter, &pipe);
-                commands.remove_resource::<PlushieInSimulation>();
-                sync_state.plushie_removed();
-                return Ok(());
-            }
-        };
+    let resumed = match (&previous_plushie, &state.snapshot) {
+        (None, Some(path)) => resume_from_snapshot(path, &msg.acl, &pipe),
+        _ => None,
+    };
+    let resumed_from_snapshot = resumed.is_some();
+    let parsed = match (resumed, &previous_plushie, state.keep_unchanged_nodes) {
+        (Some((def, plushie)), _, _) => Ok((def, plushie, 0)),
+        (None, Some(previous), true) => crochet::reparse(
+            &msg.acl,
+            HOOK_SIZE,
+            &state.initializer,
+            &previous.definition,
+            &previous.plushie,
+        ),
+        _ => crochet::parse(&msg.acl, HOOK_SIZE, &state.initializer)
+            .map(|(def, plushie)| (def, plushie, 0)),
+    };
+    let (plushie_def, simulated_plushie, kept) = match parsed {
+        Ok(x) => x,
+        Err(err) => {
+            despawn_old_plushie(&mut commands, existing_plushie_entities, &NodeLookup::new());
+            report_error(err, &mut code_editor.highlighter, &pipe);
+            commands.remove_resource::<PlushieInSimulation>();
+            sync_state.plushie_removed();
+            return Ok(());
+        }
+    };
 
     let mut node_lookup = NodeLookup::new();
+    if let Some(previous) = &previous_plushie {
+        for index in 0..kept {
+            let entity = *previous
+                .node_lookup
+                .index_to_entity
+                .get(&index)
+                .expect("index to entity should contain every node of the previous plushie");
+            node_lookup.index_to_entity.insert(index, entity);
+            node_lookup.entity_to_index.insert(entity, index);
+            // node is built the same way, but the text that created it might have moved
+            if let Ok(mut graph_node) = graph_nodes.get_mut(entity) {
+                let definition = &simulated_plushie.nodes()[index].definition;
+                graph_node.origin = definition.origin.origin;
+                graph_node.path = definition.origin.path.clone();
+                if definition.color != previous.definition.nodes[index].color {
+                    let color =
+                        assets.get_or_create_fabric_material(definition.color, &mut materials);
+                    commands
+                        .entity(graph_node.child_per_display_mode[DisplayMode::Pattern])
+                        .insert(MeshMaterial3d(color));
+                }
+            }
+        }
+    }
+    despawn_old_plushie(&mut commands, existing_plushie_entities, &node_lookup);
 
     let node_entities: Vec<Entity> = simulated_plushie
         .nodes()
         .iter()
         .enumerate()
         .map(|(node_index, node)| {
+            if node_index < kept {
+                return *node_lookup
+                    .index_to_entity
+                    .get(&node_index)
+                    .expect("index to entity should contain kept nodes");
+            }
             add_graph_node(
                 &AddGraphNode {
                     position: node.position.clone(),
                     color: node.definition.color,
-                    peculiarity: node.definition.peculiarity,
+                    peculiarity: node.definition.outside_peculiarity(),
                     origin: node.definition.origin.origin, // TODO use the full ActionWithOrigin, create a display mode for it
+                    path: node.definition.origin.path.clone(),
                     part_index: node.definition.part_index,
                     node_index,
                 },
@@ -236,22 +381,52 @@ pub fn build_plushie_from_pattern(
         definition: plushie_def.clone(),
         plushie: simulated_plushie.clone(),
         node_lookup,
+        stats: default(),
     });
+    for warning in &plushie_def.pattern.warnings {
+        pipe.write(format!("Warning in pattern: {}", warning).as_str());
+    }
     sync_state.plushie_parsed(msg.acl.clone());
     state.active_part = Some(simulated_plushie.parts()[0].name().clone());
 
     match state.initializer {
-        crochet::force_graph::Initializer::RegularCylinder(_) => {
+        _ if resumed_from_snapshot => {
+            pipe.write("Resumed a plushie from the snapshot");
+        }
+        crochet::force_graph::Initializer::RegularCylinder(_)
+        | crochet::force_graph::Initializer::Lathe => {
             pipe.write("Built a plushie");
         }
         crochet::force_graph::Initializer::OneByOne => {
             pipe.write("Started building a plushie one by one");
         }
     }
+    if kept > 0 {
+        pipe.write(format!("Kept {kept} unchanged nodes").as_str());
+    }
 
     Ok(())
 }
 
+/// `None` when there is nothing to resume, a missing file just means no snapshot was saved yet
+fn resume_from_snapshot(
+    path: &Path,
+    acl: &str,
+    pipe: &ConsolePipe,
+) -> Option<(crochet::PlushieDef, SimulatedPlushie)> {
+    let bytes = std::fs::read(path).ok()?;
+    let resumed = Snapshot::from_bytes(&bytes)
+        .map_err(crochet::errors::Error::Snapshot)
+        .and_then(|snapshot| crochet::resume(acl, &snapshot));
+    match resumed {
+        Ok(resumed) => Some(resumed),
+        Err(err) => {
+            pipe.write(format!("Not resuming from {}: {err}", path.display()).as_str());
+            None
+        }
+    }
+}
+
 pub fn continue_building_one_by_one(
     mut plushie: ResMut<PlushieInSimulation>,
     mut commands: Commands,
@@ -259,24 +434,16 @@ pub fn continue_building_one_by_one(
     mut materials: ResMut<Assets<StandardMaterial>>,
     display_presets: Res<DisplayPresets>,
     pipe: Res<ConsolePipe>,
+    state: Res<SimulationState>,
 ) {
-    match plushie.plushie.advance_one_by_one() {
-        OneByOneResult::Noop => (),
-        OneByOneResult::JustFinished => {
-            pipe.write("finished building a plushie one by one");
-        }
-        OneByOneResult::CreatedNode(new_index) => {
-            add_node_to_world(
-                &mut plushie,
-                new_index,
-                &mut commands,
-                &mut assets,
-                &mut materials,
-                &display_presets,
-            );
-        }
-        OneByOneResult::CreatedMagicRing { start, count } => {
-            for new_index in start..start + count {
+    let results = plushie.plushie.grow_one_by_one(&state.one_by_one);
+    for result in results {
+        match result {
+            OneByOneResult::Noop => (),
+            OneByOneResult::JustFinished => {
+                pipe.write("finished building a plushie one by one");
+            }
+            OneByOneResult::CreatedNode(new_index) => {
                 add_node_to_world(
                     &mut plushie,
                     new_index,
@@ -286,28 +453,40 @@ pub fn continue_building_one_by_one(
                     &display_presets,
                 );
             }
-        }
-        OneByOneResult::CreatedEdge(node_a, node_b) => {
-            let a = plushie
-                .node_lookup
-                .index_to_entity
-                .get(&node_a)
-                .expect("index to entity should contain this node durign OBO");
-            let b = plushie
-                .node_lookup
-                .index_to_entity
-                .get(&node_b)
-                .expect("index to entity should contain this node durign OBO");
+            OneByOneResult::CreatedMagicRing { start, count } => {
+                for new_index in start..start + count {
+                    add_node_to_world(
+                        &mut plushie,
+                        new_index,
+                        &mut commands,
+                        &mut assets,
+                        &mut materials,
+                        &display_presets,
+                    );
+                }
+            }
+            OneByOneResult::CreatedEdge(node_a, node_b) => {
+                let a = plushie
+                    .node_lookup
+                    .index_to_entity
+                    .get(&node_a)
+                    .expect("index to entity should contain this node durign OBO");
+                let b = plushie
+                    .node_lookup
+                    .index_to_entity
+                    .get(&node_b)
+                    .expect("index to entity should contain this node durign OBO");
 
-            add_link_between(
-                *a,
-                *b,
-                &mut commands,
-                &mut assets,
-                &mut materials,
-                plushie.plushie.nodes()[node_a.max(node_b)].definition.color,
-                &display_presets,
-            );
+                add_link_between(
+                    *a,
+                    *b,
+                    &mut commands,
+                    &mut assets,
+                    &mut materials,
+                    plushie.plushie.nodes()[node_a.max(node_b)].definition.color,
+                    &display_presets,
+                );
+            }
         }
     }
 }
@@ -324,8 +503,9 @@ fn add_node_to_world(
     let msg = AddGraphNode {
         position: new_node.position,
         color: new_node.definition.color,
-        peculiarity: new_node.definition.peculiarity,
+        peculiarity: new_node.definition.outside_peculiarity(),
         origin: new_node.definition.origin.origin,
+        path: new_node.definition.origin.path.clone(),
         node_index: new_index,
         part_index: new_node.definition.part_index,
     };
@@ -1,10 +1,35 @@
+use std::collections::VecDeque;
+
 use bevy::{platform::collections::HashMap, prelude::*};
+use crochet::force_graph::simulated_plushie::step_stats::StepStats;
 
 #[derive(Resource)]
 pub struct PlushieInSimulation {
     pub definition: crochet::PlushieDef,
     pub plushie: crochet::force_graph::simulated_plushie::SimulatedPlushie,
     pub node_lookup: NodeLookup,
+    pub stats: StepHistory,
+}
+
+/// Stats of the most recent simulation steps, oldest first
+#[derive(Default)]
+pub struct StepHistory {
+    steps: VecDeque<StepStats>,
+}
+
+impl StepHistory {
+    const LENGTH: usize = 1000;
+
+    pub fn push(&mut self, stats: StepStats) {
+        if self.steps.len() == Self::LENGTH {
+            self.steps.pop_front();
+        }
+        self.steps.push_back(stats);
+    }
+
+    pub fn steps(&self) -> &VecDeque<StepStats> {
+        &self.steps
+    }
 }
 
 pub struct NodeLookup {
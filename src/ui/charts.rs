@@ -3,15 +3,22 @@ use bevy_egui::EguiContexts;
 use crochet::force_graph::centroid_stuffing;
 use crochet::force_graph::centroid_stuffing::centroid_push_magnitude;
 use crochet::force_graph::link_force::link_force_magnitude;
+use crochet::force_graph::simulated_plushie::step_stats::StepStats;
 use egui_plot::AxisHints;
+use egui_plot::Legend;
 use egui_plot::Line;
 use egui_plot::Plot;
 use egui_plot::PlotPoints;
 
 use crate::HOOK_SIZE;
+use crate::state::simulated_plushie::{PlushieInSimulation, StepHistory};
 use crate::ui::data::UiState;
 
-pub fn chart_window(mut contexts: EguiContexts, state: Res<UiState>) -> Result {
+pub fn chart_window(
+    mut contexts: EguiContexts,
+    state: Res<UiState>,
+    plushie: Option<Res<PlushieInSimulation>>,
+) -> Result {
     if !state.charts_visible {
         return Ok(());
     }
@@ -22,6 +29,10 @@ pub fn chart_window(mut contexts: EguiContexts, state: Res<UiState>) -> Result {
         .resizable(false)
         .show(ctx, |ui| {
             CustomPlot::default().show_plot(ui);
+            if let Some(plushie) = &plushie {
+                ui.separator();
+                simulation_plots(ui, plushie);
+            }
         });
 
     Ok(())
@@ -114,3 +125,69 @@ impl CustomPlot {
         });
     }
 }
+
+/// Live metrics of the last steps, to tell when the plushie settled and how parameters change it
+fn simulation_plots(ui: &mut bevy_egui::egui::Ui, plushie: &PlushieInSimulation) {
+    let history = &plushie.stats;
+    let Some(last) = history.steps().back() else {
+        ui.label("Simulation is not running");
+        return;
+    };
+    let size = last.total.bounding_box / HOOK_SIZE;
+    ui.label(format!(
+        "Size: {:.1} x {:.1} x {:.1} stitches",
+        size.x, size.y, size.z
+    ));
+
+    ui.horizontal(|ui| {
+        ui.vertical(|ui| {
+            ui.label("Energy");
+            step_plot("energy").show(ui, |plot_ui| {
+                plot_ui.line(history_line(history, "links", |s| s.total.link_energy));
+                plot_ui.line(history_line(history, "stuffing", |s| {
+                    s.total.stuffing_energy
+                }));
+            });
+        });
+        ui.vertical(|ui| {
+            ui.label("Movement per step");
+            step_plot("movement").show(ui, |plot_ui| {
+                plot_ui.line(history_line(history, "stitches", |s| {
+                    s.total.mean_displacement / HOOK_SIZE
+                }));
+            });
+        });
+    });
+
+    ui.label("Strongest link tension");
+    step_plot("tension").show(ui, |plot_ui| {
+        for (index, part) in plushie.plushie.parts().iter().enumerate() {
+            plot_ui.line(history_line(history, part.name().clone(), move |s| {
+                s.parts.get(index).map_or(0.0, |part| part.max_tension)
+            }));
+        }
+    });
+}
+
+fn step_plot<'a>(id: &'a str) -> Plot<'a> {
+    Plot::new(id)
+        .custom_x_axes(vec![AxisHints::new_x().label("Step")])
+        .legend(Legend::default())
+        .allow_drag(false)
+        .height(150.0)
+        .width(250.0)
+}
+
+fn history_line<'a>(
+    history: &StepHistory,
+    name: impl Into<String>,
+    metric: impl Fn(&StepStats) -> f32,
+) -> Line<'a> {
+    let points: PlotPoints = history
+        .steps()
+        .iter()
+        .enumerate()
+        .map(|(step, stats)| [step as f64, metric(stats) as f64])
+        .collect();
+    Line::new(name, points)
+}
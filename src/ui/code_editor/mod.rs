@@ -90,6 +90,37 @@ pub fn code_editor_ui(
                             sync_state.editor_changed(&state.code);
                         }
 
+                        let painter = ui.painter_at(response.text_clip_rect);
+                        for (byte, label) in &state.repetition_labels {
+                            let Some(before) = state.code.get(..*byte) else {
+                                continue;
+                            };
+                            // galley cursors count chars, not bytes
+                            let cursor = egui::text::CCursor::new(before.chars().count());
+                            let token = response
+                                .galley
+                                .pos_from_cursor(cursor)
+                                .translate(response.galley_pos.to_vec2());
+                            let galley = painter.layout_no_wrap(
+                                label.clone(),
+                                egui::FontId::monospace(EDITOR_FONT_SIZE * 0.7),
+                                egui::Color32::BLACK,
+                            );
+                            let rect = egui::Align2::LEFT_BOTTOM
+                                .anchor_size(token.left_top(), galley.size())
+                                .expand(1.0);
+                            painter.rect_filled(
+                                rect,
+                                egui::CornerRadius::same(2),
+                                egui::Color32::LIGHT_YELLOW,
+                            );
+                            painter.galley(
+                                rect.min + egui::vec2(1.0, 1.0),
+                                galley,
+                                egui::Color32::BLACK,
+                            );
+                        }
+
                         if let Some(cursor_range) = response.cursor_range {
                             if !cursor_range.is_empty() {
                                 // TODO select stitches
@@ -8,6 +8,9 @@ pub struct CodeEditorState {
     pub code: String,
     pub syntax: Syntax,
     pub highlighter: Highlighter,
+    /// Labels drawn over the code at byte offsets, e.g. "4/6" over a token repeated 6 times,
+    /// when the selected node comes from its 4th repetition.
+    pub repetition_labels: Vec<(usize, String)>,
 }
 
 impl CodeEditorState {
@@ -16,6 +19,7 @@ impl CodeEditorState {
             code: acl_code,
             syntax: acl_syntax(),
             highlighter: Highlighter::new(),
+            repetition_labels: vec![],
         }
     }
 }
@@ -1,14 +1,17 @@
 mod parts_ui;
 mod right_panel;
 
-use crate::FIXED_UPDATE_BASE_HZ;
 use crate::plushie::{DisplayMode, SetDisplayMode};
+use crate::state::editor_simulation_sync::EditorSimulationSync;
 use crate::state::simulated_plushie::PlushieInSimulation;
-use crate::ui::SimulationState;
 use crate::ui::control_panel::parts_ui::parts_ui;
 use crate::ui::control_panel::right_panel::RightPanel;
 use crate::ui::ui_used_input::UiUsedInput;
 use crate::ui::utils::using_resizer;
+use crate::ui::{ConsolePipe, SimulationState};
+use crate::{FIXED_UPDATE_BASE_HZ, FLOOR, HOOK_SIZE};
+use std::path::Path;
+
 use bevy::prelude::*;
 use bevy_egui::egui::Ui;
 use bevy_egui::egui::panel::Side;
@@ -17,6 +20,11 @@ use bevy_egui::{
     egui::{self},
 };
 use crochet::force_graph::Initializer;
+use crochet::force_graph::gravity::Balance;
+use crochet::force_graph::link_force::LinkGauge;
+use crochet::force_graph::self_collision::CollisionScope;
+use crochet::force_graph::simulated_plushie::init::OneByOneParams;
+use crochet::force_graph::simulated_plushie::step::Integrator;
 
 pub fn control_panel(
     mut state: ResMut<SimulationState>,
@@ -26,6 +34,8 @@ pub fn control_panel(
     mut collapsed: Local<bool>,
     mut timestep: ResMut<Time<Fixed>>,
     mut current_plushie: Option<ResMut<PlushieInSimulation>>,
+    sync_state: Res<EditorSimulationSync>,
+    pipe: Res<ConsolePipe>,
 ) -> Result {
     let ctx = contexts.ctx_mut()?;
 
@@ -63,8 +73,15 @@ pub fn control_panel(
                 "Cylinder",
             )
             .on_hover_text("Spawn all nodes at once in a shape of a cylinder.");
+            ui.radio_value(&mut state.initializer, Initializer::Lathe, "Rounds")
+                .on_hover_text(NODE_INITIALIZATION_LATHE_HELP);
             ui.radio_value(&mut state.initializer, Initializer::OneByOne, "One by one")
                 .on_hover_text(NODE_INITIALIZATION_OBO_HELP);
+            if state.initializer == Initializer::OneByOne {
+                one_by_one(ui, &mut state.one_by_one);
+            }
+            ui.checkbox(&mut state.keep_unchanged_nodes, "Keep unchanged nodes")
+                .on_hover_text(NODE_INITIALIZATION_KEEP_HELP);
         });
         ui.collapsing("Forces", |ui| {
             ui.add(
@@ -72,10 +89,25 @@ pub fn control_panel(
                     .text("Single loop force"),
             )
             .on_hover_text(FORCES_SLF_HELP);
+            self_collision(ui, &mut state);
+        });
+        ui.collapsing("Gauge", |ui| {
+            gauge(ui, &mut state.gauge);
+        });
+        ui.collapsing("Gravity", |ui| {
+            gravity(ui, &mut state, current_plushie.as_deref());
+        });
+        ui.collapsing("Integrator", |ui| {
+            integrator(ui, &mut state);
         });
         ui.collapsing("Parts", |mut ui| {
             parts_ui(&mut ui, &mut state, &mut current_plushie);
         });
+        if let Some(path) = &state.snapshot {
+            ui.collapsing("Snapshot", |ui| {
+                snapshot(ui, path, current_plushie.as_deref(), &sync_state, &pipe);
+            });
+        }
     });
 
     // prevent world events on resizing
@@ -86,6 +118,166 @@ pub fn control_panel(
     Ok(())
 }
 
+fn self_collision(ui: &mut Ui, state: &mut SimulationState) {
+    ui.label("Self collision")
+        .on_hover_text(FORCES_SELF_COLLISION_HELP);
+    ui.radio_value(&mut state.self_collision, None, "Off");
+    ui.radio_value(
+        &mut state.self_collision,
+        Some(CollisionScope::Part),
+        "Within parts",
+    );
+    ui.radio_value(
+        &mut state.self_collision,
+        Some(CollisionScope::Cluster),
+        "Between sewn parts",
+    );
+    ui.radio_value(
+        &mut state.self_collision,
+        Some(CollisionScope::Global),
+        "Everything",
+    );
+    if state.self_collision.is_some() {
+        ui.add(
+            egui::Slider::new(&mut state.self_collision_force, 0.0..=1.0).text("Collision force"),
+        );
+    }
+}
+
+fn gauge(ui: &mut Ui, gauge: &mut LinkGauge) {
+    ui.horizontal(|ui| {
+        if ui
+            .button("Uniform")
+            .on_hover_text("Every link is one stitch long.")
+            .clicked()
+        {
+            *gauge = LinkGauge::UNIFORM;
+        }
+        if ui
+            .button("Fabric")
+            .on_hover_text(GAUGE_FABRIC_HELP)
+            .clicked()
+        {
+            *gauge = LinkGauge::FABRIC;
+        }
+    });
+    for (name, link) in [
+        ("Along the round", &mut gauge.round),
+        ("To the stitch below", &mut gauge.anchor),
+        ("Single loop", &mut gauge.single_loop_anchor),
+        ("Sewn", &mut gauge.sew),
+        ("Chain", &mut gauge.chain),
+        ("Ring center", &mut gauge.spoke),
+    ] {
+        ui.label(name);
+        ui.add(
+            egui::Slider::new(&mut link.length, 0.3..=2.0)
+                .text("Length")
+                .suffix(" st"),
+        );
+        ui.add(egui::Slider::new(&mut link.stiffness, 0.1..=2.0).text("Stiffness"));
+    }
+}
+
+fn one_by_one(ui: &mut Ui, params: &mut OneByOneParams) {
+    ui.add(egui::Slider::new(&mut params.batch, 1..=50).text("Batch"))
+        .on_hover_text("Stitches (or magic rings, or sewn links) added at once.");
+    ui.add(
+        egui::Slider::new(&mut params.settled_below, 0.0..=0.1)
+            .text("Settled below")
+            .suffix(" st"),
+    )
+    .on_hover_text(NODE_INITIALIZATION_SETTLED_HELP);
+    ui.add(egui::Slider::new(&mut params.max_steps, 0..=500).text("Max wait"))
+        .on_hover_text("Steps after which the next batch is added even if the plushie still moves. 0 adds a batch every step.");
+}
+
+fn snapshot(
+    ui: &mut Ui,
+    path: &Path,
+    plushie: Option<&PlushieInSimulation>,
+    sync_state: &EditorSimulationSync,
+    pipe: &ConsolePipe,
+) {
+    ui.label(format!("Saved to {}", path.display()));
+    let (Some(plushie), Some(acl)) = (plushie, &sync_state.acl_in_simulation) else {
+        return;
+    };
+    if ui
+        .button("Save snapshot")
+        .on_hover_text(SNAPSHOT_HELP)
+        .clicked()
+    {
+        let bytes = plushie.plushie.snapshot(acl).to_bytes();
+        match std::fs::write(path, bytes) {
+            Ok(()) => pipe.write(format!("Saved snapshot to {}", path.display()).as_str()),
+            Err(err) => pipe.write(format!("Failed to save snapshot: {err}").as_str()),
+        }
+    }
+}
+
+fn gravity(ui: &mut Ui, state: &mut SimulationState, plushie: Option<&PlushieInSimulation>) {
+    if ui
+        .button("Drop and settle")
+        .on_hover_text(GRAVITY_DROP_HELP)
+        .clicked()
+    {
+        state.gravity = Some(state.gravity.unwrap_or(DEFAULT_GRAVITY));
+        state.floor = true;
+    }
+    let mut enabled = state.gravity.is_some();
+    if ui.checkbox(&mut enabled, "Gravity").changed() {
+        state.gravity = enabled.then_some(DEFAULT_GRAVITY);
+    }
+    if let Some(strength) = &mut state.gravity {
+        ui.add(
+            egui::Slider::new(strength, 0.001..=0.2)
+                .logarithmic(true)
+                .text("Strength"),
+        )
+        .on_hover_text(GRAVITY_STRENGTH_HELP);
+    }
+    ui.checkbox(&mut state.floor, "Floor")
+        .on_hover_text("Stitches can't go below the grid.");
+
+    if state.floor
+        && let Some(plushie) = plushie
+    {
+        let balance = match plushie.plushie.balance(FLOOR) {
+            Balance::Airborne => "Not touching the floor".to_string(),
+            Balance::Stable { margin } => format!(
+                "Stands upright, {:.1} stitches from tipping over",
+                margin / HOOK_SIZE
+            ),
+            Balance::TipsOver { .. } => "Tips over".to_string(),
+        };
+        ui.label(balance);
+    }
+}
+
+fn integrator(ui: &mut Ui, state: &mut SimulationState) {
+    ui.radio_value(&mut state.integrator, Integrator::Gradient, "Gradient")
+        .on_hover_text("Move the stitches by their forces. Slow but steady.");
+    let damping = match state.integrator {
+        Integrator::Verlet { damping } => damping,
+        _ => 0.1,
+    };
+    ui.radio_value(
+        &mut state.integrator,
+        Integrator::Verlet { damping },
+        "Damped Verlet",
+    )
+    .on_hover_text("Stitches keep their momentum. Relaxes big plushies faster.");
+    ui.radio_value(&mut state.integrator, Integrator::Fire, "FIRE")
+        .on_hover_text(INTEGRATOR_FIRE_HELP);
+    if let Integrator::Verlet { damping } = &mut state.integrator {
+        ui.add(egui::Slider::new(damping, 0.01..=0.5).text("Damping"))
+            .on_hover_text(
+                "Fraction of the momentum lost every tick. Low values make the plushie wobble.",
+            );
+    }
+}
+
 fn display_mode(ui: &mut Ui, state: &mut SimulationState) {
     ui.radio_value(&mut state.display_mode, DisplayMode::Pattern, "Pattern")
         .on_hover_text("Use colors defined in the pattern. Big stitches, small links.");
@@ -100,4 +292,14 @@ fn display_mode(ui: &mut Ui, state: &mut SimulationState) {
 // long strings break rust analyzer, can't even format a file. It works if the long string is here
 // cargo clean didn't help
 const NODE_INITIALIZATION_OBO_HELP: &'static str = "Spawn the stitches one by one, waiting for the previous node to reach a relatively stable position before advancing.";
+const NODE_INITIALIZATION_LATHE_HELP: &'static str = "Spawn all nodes at once, every round as a ring as wide as its stitch count. Starts close to the final shape of parts worked in the round.";
+const NODE_INITIALIZATION_SETTLED_HELP: &'static str = "The next batch is added once the newest stitches move less than this in one step, measured in stitches.";
+const NODE_INITIALIZATION_KEEP_HELP: &'static str = "When rebuilding, only the nodes after the first change are spawned again. Unchanged nodes keep their positions.";
+const INTEGRATOR_FIRE_HELP: &'static str = "Stitches gain momentum while moving along the forces and stop as soon as they overshoot. Usually the fastest to settle.";
+const FORCES_SELF_COLLISION_HELP: &'static str = "Stitches that aren't linked push each other away when they get closer than a link. Keeps folded fabric and sewn parts from passing through each other.";
+const GRAVITY_DROP_HELP: &'static str = "Turn on gravity and the floor, the plushie falls on the grid. Once it settles, check below whether it stays upright.";
+const GRAVITY_STRENGTH_HELP: &'static str = "Pull on every stitch, compared to the pull of a fully stretched link. Stitches of parts with \"@pellets\" are heavier.";
+const GAUGE_FABRIC_HELP: &'static str = "Rough proportions of single crochet: stitches are taller than wide, the fabric stretches more along the rounds, single loop stitches are looser and sewn stitches are pulled tight.";
+const DEFAULT_GRAVITY: f32 = 0.01;
+const SNAPSHOT_HELP: &'static str = "Save the plushie as it is now. Next time the same pattern is opened, the simulation continues from here instead of building the plushie again.";
 const FORCES_SLF_HELP: &'static str = "Controls how much the \"Front loop only\" and \"Back loop only \" nodes are pushed in/out of the creation. Can cause the plushie to rotate endlessly.";
@@ -1,5 +1,6 @@
 use bevy::ecs::system::ResMut;
 use bevy_egui::egui;
+use crochet::acl::Stuffing;
 
 use crate::{
     state::simulated_plushie::PlushieInSimulation,
@@ -38,8 +39,36 @@ pub fn parts_ui(
                 }
             });
 
-        ui.add(egui::Slider::new(&mut part.centroids_wanted, 0..=20).text("Centroids"))
-            .on_hover_text(CENTROID_NUMBER_HELP);
+        let firmness = match part.stuffing {
+            Stuffing::Pressure { firmness } => firmness,
+            Stuffing::Centroids => Stuffing::DEFAULT_FIRMNESS,
+        };
+        ui.horizontal(|ui| {
+            ui.label("Stuffing");
+            ui.radio_value(&mut part.stuffing, Stuffing::Centroids, "Centroids");
+            ui.radio_value(
+                &mut part.stuffing,
+                Stuffing::Pressure { firmness },
+                "Pressure",
+            )
+            .on_hover_text(PRESSURE_STUFFING_HELP);
+        });
+        match &mut part.stuffing {
+            Stuffing::Centroids => {
+                ui.checkbox(&mut part.auto_centroids, "Automatic centroids")
+                    .on_hover_text(AUTO_CENTROIDS_HELP);
+                if part.auto_centroids {
+                    ui.label(format!("Centroids: {}", part.centroids().len()));
+                } else {
+                    ui.add(egui::Slider::new(&mut part.centroids_wanted, 0..=20).text("Centroids"))
+                        .on_hover_text(CENTROID_NUMBER_HELP);
+                }
+            }
+            Stuffing::Pressure { firmness } => {
+                ui.add(egui::Slider::new(firmness, 0.05..=2.0).text("Firmness"))
+                    .on_hover_text("How hard a squashed part pushes back");
+            }
+        }
 
         if ui.button("Select all nodes").clicked() {
             state
@@ -104,3 +133,5 @@ impl<'a> PartContext<'a> {
 }
 
 const CENTROID_NUMBER_HELP: &'static str = "Number of stuffing centroids. Bigger plushies need more centroids. Changes will be visible only if simulation is running.";
+const PRESSURE_STUFFING_HELP: &'static str = "Fill the part like a balloon, pushing its surface outwards until it's about as round as a ball. Works best for closed parts.";
+const AUTO_CENTROIDS_HELP: &'static str = "Add and remove centroids as the part grows, about one per width of the part along its length. Same as \"@centroids = auto\" in the pattern.";
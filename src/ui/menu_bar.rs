@@ -4,12 +4,16 @@ use bevy_egui::{
     egui::{self, KeyboardShortcut, Modifiers},
 };
 
-use crate::ui::{data::UiState, ui_used_input::UiUsedInput};
+use crate::ui::{
+    ConsolePipe, code_editor::state::CodeEditorState, data::UiState, ui_used_input::UiUsedInput,
+};
 
 pub fn top_panel(
     mut contexts: EguiContexts,
     ui_used_input: Res<UiUsedInput>,
     mut console_state: ResMut<UiState>,
+    mut code_editor: ResMut<CodeEditorState>,
+    pipe: Res<ConsolePipe>,
 ) -> Result {
     let ctx = contexts.ctx_mut()?;
 
@@ -54,6 +58,14 @@ pub fn top_panel(
                 if ui.button("Paste").clicked() {
                     ui.close();
                 }
+                ui.separator();
+                if ui.button("Renumber rounds").clicked() {
+                    match crochet::acl::renumber_rounds(&code_editor.code) {
+                        Ok(renumbered) => code_editor.code = renumbered,
+                        Err(e) => pipe.write(format!("Can't renumber rounds: {}", e).as_str()),
+                    }
+                    ui.close();
+                }
             });
 
             ui.menu_button("Help", |ui| {
@@ -1,7 +1,14 @@
+use std::path::PathBuf;
+
 use bevy::prelude::*;
 
 pub use crate::plushie::DisplayMode;
 use crate::{state::simulated_plushie::PlushieInSimulation, ui::action_item::UiActionItem};
+use crochet::force_graph::{
+    link_force::LinkGauge,
+    self_collision::CollisionScope,
+    simulated_plushie::{init::OneByOneParams, step::Integrator},
+};
 
 #[derive(Resource)]
 pub struct SimulationState {
@@ -10,7 +17,24 @@ pub struct SimulationState {
     pub force_multiplier: f32,
     pub display_mode: DisplayMode,
     pub single_loop_force: f32,
+    /// Rest length and stiffness of every kind of link
+    pub gauge: LinkGauge,
+    pub integrator: Integrator,
+    /// Which stitches repel each other when they get closer than a link, `None` to let them pass through.
+    pub self_collision: Option<CollisionScope>,
+    pub self_collision_force: f32,
+    /// Pull of gravity on every stitch, `None` to let the plushie float.
+    pub gravity: Option<f32>,
+    /// Nodes can't go below the grid.
+    pub floor: bool,
     pub initializer: crochet::force_graph::Initializer,
+    /// Pace of the growth with [`Initializer::OneByOne`](crochet::force_graph::Initializer::OneByOne)
+    pub one_by_one: OneByOneParams,
+    /// When rebuilding, keep the relaxed positions of nodes the new pattern builds the same way.
+    pub keep_unchanged_nodes: bool,
+    /// File the plushie is saved to from the control panel.
+    /// The first build resumes from it instead of starting over, unless the pattern changed since.
+    pub snapshot: Option<PathBuf>,
     pub active_part: Option<String>,
     pub action_items: Vec<UiActionItem>,
 }
@@ -23,7 +47,16 @@ impl Default for SimulationState {
             force_multiplier: 1.0,
             display_mode: default(),
             single_loop_force: 0.2,
+            gauge: LinkGauge::UNIFORM,
+            integrator: Integrator::Gradient,
+            self_collision: None,
+            self_collision_force: 0.5,
+            gravity: None,
+            floor: false,
             initializer: crochet::force_graph::Initializer::RegularCylinder(12),
+            one_by_one: default(),
+            keep_unchanged_nodes: false,
+            snapshot: None,
             active_part: None,
             action_items: vec![],
         }